@@ -0,0 +1,382 @@
+use crate::inference::ConfidenceInterval;
+use crate::optimize;
+use crate::regression::{self, LinearRegression};
+use crate::special;
+use crate::stats::{Statistics, StatsError};
+
+/// Error type for ARIMA models
+#[derive(Debug)]
+pub enum Error {
+    /// The series is empty, contains NaN, or has no variance
+    Stats(StatsError),
+    /// The starting autoregressive regression could not be fitted
+    Regression(regression::Error),
+    /// The series is too short for the order of the model
+    InsufficientData { required: usize, actual: usize },
+    /// Confidence levels must be strictly between 0 and 1
+    InvalidConfidence(f64),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<StatsError> for Error {
+    fn from(e: StatsError) -> Self {
+        Error::Stats(e)
+    }
+}
+
+impl From<regression::Error> for Error {
+    fn from(e: regression::Error) -> Self {
+        Error::Regression(e)
+    }
+}
+
+/// Orders of an ARIMA(p, d, q) model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArimaOrder {
+    /// Number of autoregressive terms
+    pub p: usize,
+    /// Number of times the series is differenced
+    pub d: usize,
+    /// Number of moving average terms
+    pub q: usize,
+}
+
+impl ArimaOrder {
+    pub fn new(p: usize, d: usize, q: usize) -> Self {
+        Self { p, d, q }
+    }
+}
+
+/// Criterion used to choose between model orders
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InformationCriterion {
+    /// Akaike information criterion
+    Aic,
+    /// Bayesian (Schwarz) information criterion
+    Bic,
+}
+
+/// A point forecast with its prediction interval
+#[derive(Debug, Clone, Copy)]
+pub struct Forecast {
+    /// Number of periods ahead of the last observation
+    pub step: usize,
+    pub standard_error: f64,
+    pub interval: ConfidenceInterval,
+}
+
+/// An ARIMA(p, d, q) model fitted by conditional maximum likelihood
+///
+/// The differenced series `w` follows
+/// `w_t = c + sum(phi_i * w_{t-i}) + e_t + sum(theta_j * e_{t-j})` with Gaussian errors, and the
+/// likelihood is conditioned on the first `p` values with pre-sample errors set to zero.
+#[derive(Debug, Clone)]
+pub struct Arima {
+    pub order: ArimaOrder,
+    /// Constant `c` of the differenced series
+    pub constant: f64,
+    /// Autoregressive coefficients `phi`
+    pub ar: Vec<f64>,
+    /// Moving average coefficients `theta`
+    pub ma: Vec<f64>,
+    /// Variance of the innovations
+    pub variance: f64,
+    pub log_likelihood: f64,
+    pub aic: f64,
+    pub bic: f64,
+    /// In-sample one-step-ahead errors of the differenced series, from period `p` onwards
+    pub residuals: Vec<f64>,
+    /// The undifferenced series the model was fitted to, used for forecasting
+    values: Vec<f64>,
+}
+
+/// Difference a series `d` times
+fn difference(values: &[f64], d: usize) -> Vec<f64> {
+    (0..d).fold(values.to_vec(), |series, _| {
+        series.windows(2).map(|w| w[1] - w[0]).collect()
+    })
+}
+
+/// Calculate the conditional residuals of an ARMA model from period `p` onwards
+fn arma_residuals(w: &[f64], constant: f64, ar: &[f64], ma: &[f64]) -> Vec<f64> {
+    let p = ar.len();
+    let mut residuals: Vec<f64> = Vec::with_capacity(w.len().saturating_sub(p));
+
+    for t in p..w.len() {
+        let autoregressive: f64 = ar
+            .iter()
+            .enumerate()
+            .map(|(i, phi)| phi * w[t - i - 1])
+            .sum();
+        let moving_average: f64 = ma
+            .iter()
+            .enumerate()
+            .filter_map(|(j, theta)| {
+                residuals
+                    .len()
+                    .checked_sub(j + 1)
+                    .map(|index| theta * residuals[index])
+            })
+            .sum();
+
+        residuals.push(w[t] - constant - autoregressive - moving_average);
+    }
+
+    residuals
+}
+
+impl Arima {
+    /// Fit an ARIMA model by conditional maximum likelihood (conditional sum of squares)
+    ///
+    /// Autoregressive coefficients start from a least squares fit and moving average
+    /// coefficients from zero. Stationarity and invertibility are not enforced.
+    pub fn fit<S: Statistics + ?Sized>(
+        data: &S,
+        order: ArimaOrder,
+        include_constant: bool,
+    ) -> Result<Self, Error> {
+        let values = data.try_values(1)?;
+        let ArimaOrder { p, d, q } = order;
+
+        let required = d + p + q + usize::from(include_constant) + 2;
+
+        if values.len() < required {
+            return Err(Error::InsufficientData {
+                required,
+                actual: values.len(),
+            });
+        }
+
+        let w = difference(values, d);
+
+        // Starting values from a least squares autoregression.
+        let (constant, ar) = if p > 0 {
+            let design: Vec<Vec<f64>> = (p..w.len())
+                .map(|t| (1..=p).map(|i| w[t - i]).collect())
+                .collect();
+            let regression = if include_constant {
+                LinearRegression::new()
+            } else {
+                LinearRegression::new().without_intercept()
+            };
+            let fit = regression.fit(&w[p..], &design)?;
+
+            if include_constant {
+                (fit.coefficients[0], fit.coefficients[1..].to_vec())
+            } else {
+                (0.0, fit.coefficients)
+            }
+        } else if include_constant {
+            (w.try_population_mean()?, vec![])
+        } else {
+            (0.0, vec![])
+        };
+
+        let unpack = |x: &[f64]| -> (f64, Vec<f64>, Vec<f64>) {
+            let (constant, rest) = if include_constant {
+                (x[0], &x[1..])
+            } else {
+                (0.0, x)
+            };
+
+            (constant, rest[..p].to_vec(), rest[p..].to_vec())
+        };
+
+        let sum_squares = |x: &[f64]| {
+            let (constant, ar, ma) = unpack(x);
+
+            arma_residuals(&w, constant, &ar, &ma)
+                .iter()
+                .map(|e| e * e)
+                .sum::<f64>()
+        };
+
+        let mut initial: Vec<f64> = include_constant.then_some(constant).into_iter().collect();
+        initial.extend(&ar);
+        initial.extend(vec![0.0; q]);
+
+        // A pure autoregression is already the conditional maximum likelihood estimate.
+        let estimate = if q > 0 {
+            optimize::nelder_mead(sum_squares, &initial, 1e-12, 2000 * initial.len())
+        } else {
+            initial
+        };
+
+        let (constant, ar, ma) = unpack(&estimate);
+        let residuals = arma_residuals(&w, constant, &ar, &ma);
+
+        let observations = residuals.len() as f64;
+        let variance = residuals.iter().map(|e| e * e).sum::<f64>() / observations;
+
+        if variance == 0.0 {
+            return Err(StatsError::DegenerateVariance.into());
+        }
+
+        let log_likelihood =
+            -observations / 2.0 * ((2.0 * std::f64::consts::PI * variance).ln() + 1.0);
+
+        // Coefficients plus the innovation variance.
+        let parameters = (estimate.len() + 1) as f64;
+
+        Ok(Self {
+            order,
+            constant,
+            ar,
+            ma,
+            variance,
+            log_likelihood,
+            aic: -2.0 * log_likelihood + 2.0 * parameters,
+            bic: -2.0 * log_likelihood + observations.ln() * parameters,
+            residuals,
+            values: values.to_vec(),
+        })
+    }
+
+    /// Fit an AR(p) model with a constant
+    pub fn autoregressive<S: Statistics + ?Sized>(data: &S, p: usize) -> Result<Self, Error> {
+        Self::fit(data, ArimaOrder::new(p, 0, 0), true)
+    }
+
+    /// Fit an MA(q) model with a constant
+    pub fn moving_average<S: Statistics + ?Sized>(data: &S, q: usize) -> Result<Self, Error> {
+        Self::fit(data, ArimaOrder::new(0, 0, q), true)
+    }
+
+    /// Fit every order up to `max_p` and `max_q` with `d` differences, returning the model that
+    /// minimizes the information criterion
+    pub fn select<S: Statistics + ?Sized>(
+        data: &S,
+        max_p: usize,
+        d: usize,
+        max_q: usize,
+        include_constant: bool,
+        criterion: InformationCriterion,
+    ) -> Result<Self, Error> {
+        let score = |model: &Self| match criterion {
+            InformationCriterion::Aic => model.aic,
+            InformationCriterion::Bic => model.bic,
+        };
+
+        let mut best = Self::fit(data, ArimaOrder::new(0, d, 0), include_constant)?;
+
+        for p in 0..=max_p {
+            for q in 0..=max_q {
+                if p == 0 && q == 0 {
+                    continue;
+                }
+
+                let model = Self::fit(data, ArimaOrder::new(p, d, q), include_constant)?;
+
+                if score(&model) < score(&best) {
+                    best = model;
+                }
+            }
+        }
+
+        Ok(best)
+    }
+
+    /// Long-run mean of the differenced series, `c / (1 - sum(phi))`
+    pub fn mean(&self) -> f64 {
+        self.constant / (1.0 - self.ar.iter().sum::<f64>())
+    }
+
+    /// Autoregressive coefficients of the undifferenced series, `phi(B) * (1 - B)^d`
+    fn integrated_ar(&self) -> Vec<f64> {
+        // Polynomial coefficients of 1 - phi_1 B - ... - phi_p B^p.
+        let mut polynomial: Vec<f64> = std::iter::once(1.0)
+            .chain(self.ar.iter().map(|phi| -phi))
+            .collect();
+
+        for _ in 0..self.order.d {
+            let mut product = vec![0.0; polynomial.len() + 1];
+
+            for (i, c) in polynomial.iter().enumerate() {
+                product[i] += c;
+                product[i + 1] -= c;
+            }
+
+            polynomial = product;
+        }
+
+        polynomial[1..].iter().map(|c| -c).collect()
+    }
+
+    /// Forecast the undifferenced series `horizon` periods ahead with prediction intervals
+    pub fn forecast(&self, horizon: usize, confidence: f64) -> Result<Vec<Forecast>, Error> {
+        if !(confidence > 0.0 && confidence < 1.0) {
+            return Err(Error::InvalidConfidence(confidence));
+        }
+
+        let ar = self.integrated_ar();
+        let z = special::standard_normal_inverse_cdf((1.0 + confidence) / 2.0);
+
+        // Psi weights of the infinite moving average representation give the forecast variance.
+        let mut psi = vec![1.0];
+
+        for j in 1..horizon {
+            let theta = self.ma.get(j - 1).copied().unwrap_or(0.0);
+            let weight = theta
+                + ar.iter()
+                    .take(j)
+                    .enumerate()
+                    .map(|(i, phi)| phi * psi[j - i - 1])
+                    .sum::<f64>();
+
+            psi.push(weight);
+        }
+
+        let mut history = self.values.clone();
+        let mut errors = self.residuals.clone();
+        let mut cumulative_psi = 0.0;
+        let mut forecasts = Vec::with_capacity(horizon);
+
+        for step in 1..=horizon {
+            let t = history.len();
+            let autoregressive: f64 = ar
+                .iter()
+                .enumerate()
+                .map(|(i, phi)| phi * history[t - i - 1])
+                .sum();
+            let moving_average: f64 = self
+                .ma
+                .iter()
+                .enumerate()
+                .filter_map(|(j, theta)| {
+                    errors
+                        .len()
+                        .checked_sub(j + 1)
+                        .map(|index| theta * errors[index])
+                })
+                .sum();
+
+            let mean = self.constant + autoregressive + moving_average;
+            cumulative_psi += psi[step - 1].powi(2);
+            let standard_error = (self.variance * cumulative_psi).sqrt();
+
+            forecasts.push(Forecast {
+                step,
+                standard_error,
+                interval: ConfidenceInterval {
+                    estimate: mean,
+                    lower: mean - z * standard_error,
+                    upper: mean + z * standard_error,
+                    confidence,
+                },
+            });
+
+            // Future errors have an expected value of zero.
+            history.push(mean);
+            errors.push(0.0);
+        }
+
+        Ok(forecasts)
+    }
+}
@@ -7,8 +7,8 @@ pub enum Error {
     InvalidMaturityDate,
 }
 
-#[derive(Clone)]
 /// Frequency of the compounding period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Frequency {
     Annual,
     SemiAnnual,
@@ -0,0 +1,75 @@
+use crate::special;
+
+/// Calculate `n!` exactly, or `None` if it overflows
+pub fn factorial(n: u64) -> Option<u128> {
+    (1..=n as u128).try_fold(1_u128, |acc, k| acc.checked_mul(k))
+}
+
+/// Calculate the number of ways to choose `r` of `n` options when order does not matter.
+///
+/// Returns `None` if the result overflows; use `ln_combinations` for large inputs.
+pub fn combinations(n: u64, r: u64) -> Option<u128> {
+    if r > n {
+        return Some(0);
+    }
+
+    // C(n, r) = C(n, n - r), so iterate over the smaller of the two.
+    let r = r.min(n - r) as u128;
+    let n = n as u128;
+    let mut result: u128 = 1;
+
+    for i in 1..=r {
+        // Divide out the common factor first so the intermediate product stays small.
+        let numerator = n - r + i;
+        let divisor = gcd(result, i);
+        let result_part = result / divisor;
+        let i_part = i / divisor;
+
+        result = result_part.checked_mul(numerator / i_part)?;
+    }
+
+    Some(result)
+}
+
+/// Calculate the number of ways to choose `r` of `n` options when order matters.
+///
+/// Returns `None` if the result overflows; use `ln_permutations` for large inputs.
+pub fn permutations(n: u64, r: u64) -> Option<u128> {
+    if r > n {
+        return Some(0);
+    }
+
+    ((n - r + 1) as u128..=n as u128).try_fold(1_u128, |acc, k| acc.checked_mul(k))
+}
+
+/// Natural logarithm of `n!`
+pub fn ln_factorial(n: u64) -> f64 {
+    special::ln_gamma(n as f64 + 1.0)
+}
+
+/// Natural logarithm of the number of combinations, for inputs too large to count exactly
+pub fn ln_combinations(n: u64, r: u64) -> f64 {
+    if r > n {
+        return f64::NEG_INFINITY;
+    }
+
+    ln_factorial(n) - ln_factorial(r) - ln_factorial(n - r)
+}
+
+/// Natural logarithm of the number of permutations, for inputs too large to count exactly
+pub fn ln_permutations(n: u64, r: u64) -> f64 {
+    if r > n {
+        return f64::NEG_INFINITY;
+    }
+
+    ln_factorial(n) - ln_factorial(n - r)
+}
+
+/// Greatest common divisor
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
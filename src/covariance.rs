@@ -0,0 +1,388 @@
+use crate::matrix::{self, Matrix};
+use crate::portfolio::{Portfolio, Ticker};
+use crate::rolling::RISKMETRICS_LAMBDA;
+use crate::stats::{RunningCovariance, Statistics, StatsError};
+
+/// Error type for covariance matrix estimates
+#[derive(Debug)]
+pub enum Error {
+    /// A column of returns is too short, contains NaN, or the columns differ in length
+    Stats(StatsError),
+    /// The portfolio has no assets, or no dates on which every asset has a return
+    EmptyPortfolio,
+    /// Only sample covariances can be estimated from pairwise aligned returns
+    UnsupportedAlignment,
+    /// The decay factor must be strictly between 0 and 1
+    InvalidLambda(f64),
+    /// The number of weights does not match the number of assets
+    DimensionMismatch { expected: usize, actual: usize },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<StatsError> for Error {
+    fn from(e: StatsError) -> Self {
+        Error::Stats(e)
+    }
+}
+
+/// How the returns of assets with different trading dates are matched up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Alignment {
+    /// Use only the dates on which every asset has a return
+    #[default]
+    CompleteCase,
+    /// Use the dates on which both assets of each pair have a return, which uses more of the
+    /// data but may give a matrix that is not positive semi-definite
+    Pairwise,
+}
+
+/// Estimator of the covariance matrix
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CovarianceMethod {
+    /// Sample covariance, with `n - 1` degrees of freedom
+    #[default]
+    Sample,
+    /// Ledoit and Wolf (2004) shrinkage of the covariance towards a scaled identity matrix,
+    /// with the intensity that minimizes the expected squared error
+    LedoitWolf,
+    /// RiskMetrics exponentially weighted covariance, assuming zero mean returns
+    Ewma { lambda: f64 },
+}
+
+impl CovarianceMethod {
+    /// RiskMetrics exponentially weighted covariance with the daily decay factor of 0.94
+    pub fn riskmetrics() -> Self {
+        CovarianceMethod::Ewma {
+            lambda: RISKMETRICS_LAMBDA,
+        }
+    }
+}
+
+/// A covariance matrix of labelled series
+#[derive(Debug, Clone)]
+pub struct CovarianceMatrix {
+    /// Label of each row and column, such as the ticker of an asset
+    pub labels: Vec<String>,
+    pub matrix: Matrix,
+    /// Intensity of the shrinkage towards the target, between 0 and 1, for Ledoit-Wolf estimates
+    pub shrinkage: Option<f64>,
+}
+
+/// Sample covariance of two columns, checking there are enough values
+fn sample_covariance(x: &[f64], y: &[f64]) -> Result<f64, StatsError> {
+    x.try_values(2)?;
+    y.try_values(2)?;
+
+    Ok(RunningCovariance::from_values(x, y).sample_covariance())
+}
+
+/// Ledoit-Wolf shrinkage of the covariance of equal-length columns, returning the shrunk matrix
+/// and the shrinkage intensity
+fn ledoit_wolf(columns: &[Vec<f64>]) -> Result<(Matrix, f64), StatsError> {
+    let p = columns.len();
+    let n = columns[0].len() as f64;
+
+    let centered: Vec<Vec<f64>> = columns
+        .iter()
+        .map(|c| {
+            let mean = c.try_population_mean()?;
+            Ok(c.iter().map(|v| v - mean).collect())
+        })
+        .collect::<Result<_, StatsError>>()?;
+
+    // Covariance with `n` degrees of freedom, as in the paper.
+    let sample: Matrix = (0..p)
+        .map(|i| {
+            (0..p)
+                .map(|j| {
+                    centered[i]
+                        .iter()
+                        .zip(&centered[j])
+                        .map(|(x, y)| x * y)
+                        .sum::<f64>()
+                        / n
+                })
+                .collect()
+        })
+        .collect();
+
+    let mu = (0..p).map(|i| sample[i][i]).sum::<f64>() / p as f64;
+    let target = |i: usize, j: usize| if i == j { mu } else { 0.0 };
+
+    let dispersion: f64 = (0..p)
+        .flat_map(|i| (0..p).map(move |j| (i, j)))
+        .map(|(i, j)| (sample[i][j] - target(i, j)).powi(2))
+        .sum();
+
+    // Variance of the sample covariance, from the spread of the outer products of each
+    // observation around it.
+    let estimation_error: f64 = (0..columns[0].len())
+        .map(|k| {
+            (0..p)
+                .flat_map(|i| (0..p).map(move |j| (i, j)))
+                .map(|(i, j)| (centered[i][k] * centered[j][k] - sample[i][j]).powi(2))
+                .sum::<f64>()
+        })
+        .sum::<f64>()
+        / (n * n);
+
+    let shrinkage = if dispersion == 0.0 {
+        0.0
+    } else {
+        estimation_error.min(dispersion) / dispersion
+    };
+
+    let shrunk = (0..p)
+        .map(|i| {
+            (0..p)
+                .map(|j| shrinkage * target(i, j) + (1.0 - shrinkage) * sample[i][j])
+                .collect()
+        })
+        .collect();
+
+    Ok((shrunk, shrinkage))
+}
+
+/// Exponentially weighted covariance of equal-length columns, matching
+/// `RollingStatistics::ewma_variance` on the diagonal
+fn ewma(columns: &[Vec<f64>], lambda: f64) -> Matrix {
+    let p = columns.len();
+
+    (0..p)
+        .map(|i| {
+            (0..p)
+                .map(|j| {
+                    columns[i].iter().zip(&columns[j]).enumerate().fold(
+                        f64::NAN,
+                        |covariance, (t, (x, y))| {
+                            if t == 0 {
+                                x * y
+                            } else {
+                                lambda * covariance + (1.0 - lambda) * x * y
+                            }
+                        },
+                    )
+                })
+                .collect()
+        })
+        .collect()
+}
+
+impl CovarianceMatrix {
+    /// Estimate the covariance matrix of equal-length columns of returns, one per label
+    pub fn estimate(
+        labels: Vec<String>,
+        columns: &[Vec<f64>],
+        method: CovarianceMethod,
+    ) -> Result<Self, Error> {
+        let first = columns.first().ok_or(StatsError::EmptyInput)?;
+
+        if labels.len() != columns.len() {
+            return Err(Error::DimensionMismatch {
+                expected: columns.len(),
+                actual: labels.len(),
+            });
+        }
+
+        for column in columns {
+            column.try_values(2)?;
+
+            if column.len() != first.len() {
+                return Err(StatsError::LengthMismatch {
+                    expected: first.len(),
+                    actual: column.len(),
+                }
+                .into());
+            }
+        }
+
+        let (matrix, shrinkage) = match method {
+            CovarianceMethod::Sample => {
+                let matrix = columns
+                    .iter()
+                    .map(|x| columns.iter().map(|y| sample_covariance(x, y)).collect())
+                    .collect::<Result<_, _>>()?;
+
+                (matrix, None)
+            }
+            CovarianceMethod::LedoitWolf => {
+                let (matrix, shrinkage) = ledoit_wolf(columns)?;
+
+                (matrix, Some(shrinkage))
+            }
+            CovarianceMethod::Ewma { lambda } => {
+                if !(lambda > 0.0 && lambda < 1.0) {
+                    return Err(Error::InvalidLambda(lambda));
+                }
+
+                (ewma(columns, lambda), None)
+            }
+        };
+
+        Ok(Self {
+            labels,
+            matrix,
+            shrinkage,
+        })
+    }
+
+    /// Estimate the covariance matrix of the returns of every asset in a portfolio, labelled
+    /// and ordered by ticker
+    pub fn from_portfolio(
+        portfolio: &Portfolio,
+        method: CovarianceMethod,
+        alignment: Alignment,
+    ) -> Result<Self, Error> {
+        let tickers: Vec<Ticker> = portfolio.tickers();
+
+        if tickers.is_empty() {
+            return Err(Error::EmptyPortfolio);
+        }
+
+        match alignment {
+            Alignment::CompleteCase => {
+                let returns = portfolio.aligned_returns();
+
+                if returns.is_empty() {
+                    return Err(Error::EmptyPortfolio);
+                }
+
+                let columns: Vec<Vec<f64>> = (0..tickers.len())
+                    .map(|i| returns.iter().map(|(_, r)| r[i]).collect())
+                    .collect();
+
+                Self::estimate(tickers, &columns, method)
+            }
+            Alignment::Pairwise => {
+                if method != CovarianceMethod::Sample {
+                    return Err(Error::UnsupportedAlignment);
+                }
+
+                let returns = portfolio.dated_asset_returns();
+                let matrix = returns
+                    .iter()
+                    .map(|x| {
+                        returns
+                            .iter()
+                            .map(|y| {
+                                let mut dates: Vec<_> =
+                                    x.keys().filter(|date| y.contains_key(date)).collect();
+                                dates.sort();
+
+                                let (x, y): (Vec<f64>, Vec<f64>) =
+                                    dates.iter().map(|date| (x[date], y[date])).unzip();
+
+                                sample_covariance(&x, &y)
+                            })
+                            .collect()
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                Ok(Self {
+                    labels: tickers,
+                    matrix,
+                    shrinkage: None,
+                })
+            }
+        }
+    }
+
+    /// Return the covariance between two labelled series
+    pub fn get(&self, a: &str, b: &str) -> Option<f64> {
+        let i = self.labels.iter().position(|l| l == a)?;
+        let j = self.labels.iter().position(|l| l == b)?;
+
+        Some(self.matrix[i][j])
+    }
+
+    /// Standard deviation of each series
+    pub fn std_devs(&self) -> Vec<f64> {
+        (0..self.matrix.len())
+            .map(|i| self.matrix[i][i].sqrt())
+            .collect()
+    }
+
+    /// Convert the covariances to correlations
+    pub fn correlation(&self) -> Matrix {
+        let std_devs = self.std_devs();
+
+        self.matrix
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(j, c)| {
+                        if i == j {
+                            1.0
+                        } else {
+                            c / (std_devs[i] * std_devs[j])
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Calculate the variance of a weighted combination of the series, `w' Σ w`
+    pub fn portfolio_variance(&self, weights: &[f64]) -> Result<f64, Error> {
+        if weights.len() != self.matrix.len() {
+            return Err(Error::DimensionMismatch {
+                expected: self.matrix.len(),
+                actual: weights.len(),
+            });
+        }
+
+        Ok(matrix::quadratic_form(&self.matrix, weights))
+    }
+
+    /// Whether the matrix has a Cholesky factorization, as required by optimizers and
+    /// simulations
+    pub fn is_positive_definite(&self) -> bool {
+        matrix::cholesky(&self.matrix).is_some()
+    }
+
+    /// Return the nearest symmetric matrix, in the Frobenius norm, whose eigenvalues are at
+    /// least `min_eigenvalue`
+    ///
+    /// Negative eigenvalues from pairwise alignment or rounding are raised to the floor, which
+    /// should be positive for a positive definite result.
+    pub fn nearest_positive_definite(&self, min_eigenvalue: f64) -> Self {
+        let n = self.matrix.len();
+        let symmetric: Matrix = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| (self.matrix[i][j] + self.matrix[j][i]) / 2.0)
+                    .collect()
+            })
+            .collect();
+
+        let (values, vectors) = matrix::symmetric_eigen(&symmetric);
+        let values: Vec<f64> = values.iter().map(|v| v.max(min_eigenvalue)).collect();
+
+        let matrix = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| {
+                        (0..n)
+                            .map(|k| vectors[i][k] * values[k] * vectors[j][k])
+                            .sum()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            labels: self.labels.clone(),
+            matrix,
+            shrinkage: self.shrinkage,
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use crate::methods::{self, net_present_value, PaymentTiming};
+
+/// Error type for discounted cash flow valuation
+#[derive(Debug)]
+pub enum Error {
+    /// At least one projected free cash flow is required
+    NoCashFlows,
+    /// Capital weights must be non-negative and sum to a positive value
+    InvalidCapitalWeights,
+    /// The discount rate must exceed the terminal growth rate
+    NonConvergentTerminalValue,
+    /// Shares outstanding must be greater than zero
+    InvalidSharesOutstanding,
+    /// A time-value-of-money calculation failed
+    Methods(methods::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<methods::Error> for Error {
+    fn from(e: methods::Error) -> Self {
+        Error::Methods(e)
+    }
+}
+
+/// Calculate the cost of equity with the Capital Asset Pricing Model (CAPM)
+pub fn capm_cost_of_equity(risk_free_rate: f64, beta: f64, expected_market_return: f64) -> f64 {
+    risk_free_rate + beta * (expected_market_return - risk_free_rate)
+}
+
+/// Inputs for the weighted average cost of capital (WACC)
+#[derive(Debug, Clone)]
+pub struct CostOfCapital {
+    /// Required return on equity, e.g. from `capm_cost_of_equity`
+    pub cost_of_equity: f64,
+    /// Pre-tax cost of debt
+    pub cost_of_debt: f64,
+    /// Marginal tax rate applied to the interest tax shield
+    pub tax_rate: f64,
+    /// Market value of equity
+    pub equity_value: f64,
+    /// Market value of debt
+    pub debt_value: f64,
+}
+
+impl CostOfCapital {
+    /// Proportion of equity in the capital structure
+    pub fn equity_weight(&self) -> f64 {
+        self.equity_value / (self.equity_value + self.debt_value)
+    }
+
+    /// Proportion of debt in the capital structure
+    pub fn debt_weight(&self) -> f64 {
+        self.debt_value / (self.equity_value + self.debt_value)
+    }
+
+    /// Calculate the weighted average cost of capital
+    pub fn wacc(&self) -> Result<f64, Error> {
+        if self.equity_value < 0.0
+            || self.debt_value < 0.0
+            || self.equity_value + self.debt_value <= 0.0
+        {
+            return Err(Error::InvalidCapitalWeights);
+        }
+
+        Ok(self.equity_weight() * self.cost_of_equity
+            + self.debt_weight() * self.cost_of_debt * (1.0 - self.tax_rate))
+    }
+}
+
+/// Method for valuing the cash flows beyond the explicit forecast period
+#[derive(Debug, Clone)]
+pub enum TerminalValue {
+    /// Final cash flow grows at a constant rate in perpetuity (Gordon growth)
+    GordonGrowth { growth_rate: f64 },
+    /// Multiple applied to a final-year metric, e.g. EV/EBITDA
+    ExitMultiple { multiple: f64, terminal_metric: f64 },
+}
+
+/// Discounted cash flow (DCF) model of a company
+#[derive(Debug, Clone)]
+pub struct DiscountedCashFlow {
+    /// Projected free cash flows, one per period starting at period 1
+    pub free_cash_flows: Vec<f64>,
+    /// Discount rate applied to the cash flows, typically the WACC
+    pub discount_rate: f64,
+    /// Terminal value method
+    pub terminal_value: TerminalValue,
+    /// Debt less cash, subtracted from enterprise value to find equity value
+    pub net_debt: f64,
+    /// Number of shares outstanding
+    pub shares_outstanding: f64,
+}
+
+/// Result of a discounted cash flow valuation
+#[derive(Debug, Clone)]
+pub struct Valuation {
+    /// Present value of the projected free cash flows
+    pub present_value_cash_flows: f64,
+    /// Terminal value at the end of the forecast period
+    pub terminal_value: f64,
+    /// Present value of the terminal value
+    pub present_value_terminal_value: f64,
+    /// Enterprise value of the company
+    pub enterprise_value: f64,
+    /// Equity value of the company
+    pub equity_value: f64,
+    /// Equity value per share
+    pub per_share_value: f64,
+}
+
+/// Per-share values over a grid of discount rates (rows) and terminal growth rates (columns)
+#[derive(Debug, Clone)]
+pub struct SensitivityTable {
+    pub discount_rates: Vec<f64>,
+    pub growth_rates: Vec<f64>,
+    /// `None` where the discount rate does not exceed the growth rate
+    pub per_share_values: Vec<Vec<Option<f64>>>,
+}
+
+impl DiscountedCashFlow {
+    /// Calculate the valuation of the company
+    pub fn valuation(&self) -> Result<Valuation, Error> {
+        self.valuation_with(self.discount_rate, &self.terminal_value)
+    }
+
+    /// Calculate the valuation for a given discount rate and terminal value method
+    pub fn valuation_with(
+        &self,
+        discount_rate: f64,
+        terminal_value: &TerminalValue,
+    ) -> Result<Valuation, Error> {
+        let final_cash_flow = *self.free_cash_flows.last().ok_or(Error::NoCashFlows)?;
+
+        if self.shares_outstanding <= 0.0 {
+            return Err(Error::InvalidSharesOutstanding);
+        }
+
+        let periods = self.free_cash_flows.len() as f64;
+
+        let terminal_value = match terminal_value {
+            TerminalValue::GordonGrowth { growth_rate } => {
+                if discount_rate <= *growth_rate {
+                    return Err(Error::NonConvergentTerminalValue);
+                }
+
+                methods::present_value_growing_perpetuity(
+                    final_cash_flow * (1.0 + growth_rate),
+                    discount_rate,
+                    *growth_rate,
+                    PaymentTiming::End,
+                )?
+            }
+            TerminalValue::ExitMultiple {
+                multiple,
+                terminal_metric,
+            } => multiple * terminal_metric,
+        };
+
+        let present_value_cash_flows =
+            net_present_value(0.0, self.free_cash_flows.clone(), discount_rate);
+        let present_value_terminal_value =
+            terminal_value * methods::discount_factor(discount_rate, periods);
+
+        let enterprise_value = present_value_cash_flows + present_value_terminal_value;
+        let equity_value = enterprise_value - self.net_debt;
+
+        Ok(Valuation {
+            present_value_cash_flows,
+            terminal_value,
+            present_value_terminal_value,
+            enterprise_value,
+            equity_value,
+            per_share_value: equity_value / self.shares_outstanding,
+        })
+    }
+
+    /// Build a table of per-share values over discount rates and Gordon terminal growth rates
+    pub fn sensitivity(
+        &self,
+        discount_rates: &[f64],
+        growth_rates: &[f64],
+    ) -> Result<SensitivityTable, Error> {
+        let mut per_share_values = Vec::with_capacity(discount_rates.len());
+
+        for discount_rate in discount_rates {
+            let mut row = Vec::with_capacity(growth_rates.len());
+
+            for growth_rate in growth_rates {
+                let terminal_value = TerminalValue::GordonGrowth {
+                    growth_rate: *growth_rate,
+                };
+
+                match self.valuation_with(*discount_rate, &terminal_value) {
+                    Ok(valuation) => row.push(Some(valuation.per_share_value)),
+                    Err(Error::NonConvergentTerminalValue) => row.push(None),
+                    Err(e) => return Err(e),
+                }
+            }
+
+            per_share_values.push(row);
+        }
+
+        Ok(SensitivityTable {
+            discount_rates: discount_rates.to_vec(),
+            growth_rates: growth_rates.to_vec(),
+            per_share_values,
+        })
+    }
+}
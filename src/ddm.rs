@@ -0,0 +1,233 @@
+use crate::methods::{self, PaymentTiming};
+use crate::optimize;
+use crate::stock_data::StockData;
+
+/// Error type for dividend discount models
+#[derive(Debug)]
+pub enum Error {
+    /// The required return must exceed the long-term growth rate
+    NonConvergentGrowth,
+    /// Not enough dividend history to estimate a growth rate
+    InsufficientDividendHistory,
+    /// No required return equates the model value with the price
+    NoSolution,
+    /// The share price must be positive and finite
+    InvalidPrice(f64),
+    /// A time-value-of-money calculation failed
+    Methods(methods::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<methods::Error> for Error {
+    fn from(e: methods::Error) -> Self {
+        Error::Methods(e)
+    }
+}
+
+/// A period of constant dividend growth in a multi-stage model
+#[derive(Debug, Clone)]
+pub struct GrowthStage {
+    /// Number of years the growth rate applies
+    pub periods: usize,
+    /// Annual dividend growth rate during the stage
+    pub growth_rate: f64,
+}
+
+/// Dividend discount model (DDM) for valuing equity from expected dividends
+#[derive(Debug, Clone)]
+pub enum DividendDiscountModel {
+    /// Single-stage model where the next dividend grows at a constant rate forever
+    GordonGrowth {
+        next_dividend: f64,
+        growth_rate: f64,
+    },
+    /// Explicit growth stages followed by constant terminal growth
+    MultiStage {
+        current_dividend: f64,
+        stages: Vec<GrowthStage>,
+        terminal_growth_rate: f64,
+    },
+    /// Growth declines linearly from a short-term to a long-term rate over `2 * half_life` years
+    HModel {
+        current_dividend: f64,
+        short_term_growth_rate: f64,
+        long_term_growth_rate: f64,
+        half_life: f64,
+    },
+}
+
+impl DividendDiscountModel {
+    /// Calculate the intrinsic value of the share given a required return
+    pub fn value(&self, required_return: f64) -> Result<f64, Error> {
+        if required_return <= self.long_term_growth_rate() {
+            return Err(Error::NonConvergentGrowth);
+        }
+
+        match self {
+            DividendDiscountModel::GordonGrowth {
+                next_dividend,
+                growth_rate,
+            } => Ok(methods::present_value_growing_perpetuity(
+                *next_dividend,
+                required_return,
+                *growth_rate,
+                PaymentTiming::End,
+            )?),
+            DividendDiscountModel::MultiStage {
+                current_dividend,
+                stages,
+                terminal_growth_rate,
+            } => {
+                let mut dividend = *current_dividend;
+                let mut dividends = vec![];
+
+                for stage in stages {
+                    for _ in 0..stage.periods {
+                        dividend *= 1.0 + stage.growth_rate;
+                        dividends.push(dividend);
+                    }
+                }
+
+                let terminal_value = methods::present_value_growing_perpetuity(
+                    dividend * (1.0 + terminal_growth_rate),
+                    required_return,
+                    *terminal_growth_rate,
+                    PaymentTiming::End,
+                )?;
+
+                Ok(
+                    methods::net_present_value(0.0, dividends.clone(), required_return)
+                        + terminal_value
+                            * methods::discount_factor(required_return, dividends.len() as f64),
+                )
+            }
+            DividendDiscountModel::HModel {
+                current_dividend,
+                short_term_growth_rate,
+                long_term_growth_rate,
+                half_life,
+            } => {
+                let spread = required_return - long_term_growth_rate;
+
+                Ok(current_dividend * (1.0 + long_term_growth_rate) / spread
+                    + current_dividend
+                        * half_life
+                        * (short_term_growth_rate - long_term_growth_rate)
+                        / spread)
+            }
+        }
+    }
+
+    /// Long-term growth rate the required return must exceed
+    fn long_term_growth_rate(&self) -> f64 {
+        match self {
+            DividendDiscountModel::GordonGrowth { growth_rate, .. } => *growth_rate,
+            DividendDiscountModel::MultiStage {
+                terminal_growth_rate,
+                ..
+            } => *terminal_growth_rate,
+            DividendDiscountModel::HModel {
+                long_term_growth_rate,
+                ..
+            } => *long_term_growth_rate,
+        }
+    }
+
+    /// Solve for the required return implied by the current share price
+    pub fn implied_required_return(&self, price: f64) -> Result<f64, Error> {
+        if !(price.is_finite() && price > 0.0) {
+            return Err(Error::InvalidPrice(price));
+        }
+
+        if let DividendDiscountModel::GordonGrowth {
+            next_dividend,
+            growth_rate,
+        } = self
+        {
+            return Ok(next_dividend / price + growth_rate);
+        }
+
+        // The value falls from infinity as the required return rises above long-term growth.
+        let lower = self.long_term_growth_rate() + 1e-9;
+
+        optimize::bisection(
+            |r| self.value(r).map(|v| v - price).unwrap_or(f64::INFINITY),
+            lower,
+            lower + 10.0,
+        )
+        .ok_or(Error::NoSolution)
+    }
+}
+
+/// Dividend payment amounts with the number of payments per year, inferred from the median
+/// time between payments
+fn regular_payments(stock_data: &StockData) -> Result<(Vec<f64>, usize), Error> {
+    let dividends = stock_data.dividends();
+
+    if dividends.len() < 2 || dividends.iter().any(|(_, d)| *d <= 0.0) {
+        return Err(Error::InsufficientDividendHistory);
+    }
+
+    let mut gaps: Vec<i64> = dividends
+        .windows(2)
+        .map(|w| (w[1].0 - w[0].0).num_days())
+        .collect();
+    gaps.sort();
+
+    let median_gap = gaps[gaps.len() / 2].max(1) as f64;
+    let frequency = ((365.25 / median_gap).round() as usize).max(1);
+
+    Ok((dividends.into_iter().map(|(_, d)| d).collect(), frequency))
+}
+
+/// Compound annual growth rate of dividends, comparing payments over like periods
+///
+/// With more than a year of payments, the first and last trailing-year totals are compared.
+/// Otherwise the first and last payments are compared and the growth is annualized by the
+/// payment frequency. Calendar-year totals are not used because the first and last years of the
+/// data are usually partial.
+pub fn compound_dividend_growth(stock_data: &StockData) -> Result<f64, Error> {
+    let (payments, frequency) = regular_payments(stock_data)?;
+    let n = payments.len();
+
+    if n > frequency {
+        let first: f64 = payments[..frequency].iter().sum();
+        let last: f64 = payments[n - frequency..].iter().sum();
+        let years = (n - frequency) as f64 / frequency as f64;
+
+        return Ok((last / first).powf(1.0 / years) - 1.0);
+    }
+
+    Ok((payments[n - 1] / payments[0]).powf(frequency as f64 / (n - 1) as f64) - 1.0)
+}
+
+/// Arithmetic average of the annual growth in dividends, comparing payments over like periods
+///
+/// With more than a year of payments, each payment is compared with the payment a year earlier.
+/// Otherwise consecutive payments are compared and the growth is annualized by the payment
+/// frequency.
+pub fn average_dividend_growth(stock_data: &StockData) -> Result<f64, Error> {
+    let (payments, frequency) = regular_payments(stock_data)?;
+
+    let growth_rates: Vec<f64> = if payments.len() > frequency {
+        payments
+            .iter()
+            .zip(&payments[frequency..])
+            .map(|(earlier, later)| later / earlier - 1.0)
+            .collect()
+    } else {
+        payments
+            .windows(2)
+            .map(|w| (w[1] / w[0]).powi(frequency as i32) - 1.0)
+            .collect()
+    };
+
+    Ok(growth_rates.iter().sum::<f64>() / growth_rates.len() as f64)
+}
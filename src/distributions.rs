@@ -0,0 +1,834 @@
+use rand::Rng;
+
+use crate::combinatorics;
+use crate::optimize;
+use crate::quantile::{QuantileMethod, QuantileStatistics};
+use crate::special;
+use crate::stats::{Statistics, StatsError};
+
+/// Error type for probability distributions
+#[derive(Debug)]
+pub enum Error {
+    /// A distribution parameter is outside of its valid range
+    InvalidParameter,
+    /// Probabilities must be between 0 and 1
+    InvalidProbability(f64),
+    /// The data cannot be fitted, e.g. non-positive values for a lognormal distribution
+    InvalidData,
+    /// The data is empty, contains NaN, or has no variance
+    Stats(StatsError),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<StatsError> for Error {
+    fn from(e: StatsError) -> Self {
+        Error::Stats(e)
+    }
+}
+
+/// A continuous probability distribution
+pub trait ContinuousDistribution {
+    /// Probability density function
+    fn pdf(&self, x: f64) -> f64;
+
+    /// Cumulative distribution function
+    fn cdf(&self, x: f64) -> f64;
+
+    /// Inverse of the cumulative distribution function (quantile function)
+    fn inverse_cdf(&self, p: f64) -> f64;
+
+    /// Mean of the distribution
+    fn mean(&self) -> f64;
+
+    /// Variance of the distribution
+    fn variance(&self) -> f64;
+
+    /// Standard deviation of the distribution
+    fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Inverse cumulative distribution function, checking the probability is valid
+    fn try_inverse_cdf(&self, p: f64) -> Result<f64, Error> {
+        if !(0.0..=1.0).contains(&p) {
+            return Err(Error::InvalidProbability(p));
+        }
+
+        Ok(self.inverse_cdf(p))
+    }
+
+    /// Draw a random value by inverse transform sampling
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        self.inverse_cdf(rng.gen::<f64>())
+    }
+
+    /// Draw `n` random values
+    fn sample_n<R: Rng + ?Sized>(&self, rng: &mut R, n: usize) -> Vec<f64> {
+        (0..n).map(|_| self.sample(rng)).collect()
+    }
+
+    /// Log-likelihood of a set of observations
+    fn log_likelihood(&self, values: &[f64]) -> f64 {
+        values.iter().map(|v| self.pdf(*v).ln()).sum()
+    }
+}
+
+/// Compare the empirical quantiles of the data with the quantiles of a fitted distribution.
+///
+/// Returns `(probability, empirical quantile, fitted quantile)` for each probability.
+pub fn compare_quantiles<D: ContinuousDistribution, S: Statistics + ?Sized>(
+    distribution: &D,
+    data: &S,
+    probabilities: &[f64],
+) -> Result<Vec<(f64, f64, f64)>, Error> {
+    let empirical = data.try_quantiles(probabilities, QuantileMethod::Type7)?;
+
+    probabilities
+        .iter()
+        .zip(empirical)
+        .map(|(p, q)| Ok((*p, q, distribution.try_inverse_cdf(*p)?)))
+        .collect()
+}
+
+/// Invert a cumulative distribution function numerically over a bracket
+fn invert_cdf<F: Fn(f64) -> f64>(cdf: F, p: f64, mut lower: f64, mut upper: f64) -> f64 {
+    // Widen the bracket until it contains the quantile.
+    while cdf(lower) > p {
+        lower = if lower < 0.0 {
+            lower * 2.0
+        } else {
+            lower / 2.0
+        };
+    }
+
+    while cdf(upper) < p {
+        upper *= 2.0;
+    }
+
+    optimize::bisection(|x| cdf(x) - p, lower, upper).unwrap_or(f64::NAN)
+}
+
+/// Normal (Gaussian) distribution
+#[derive(Debug, Clone, Copy)]
+pub struct Normal {
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+impl Normal {
+    pub fn new(mean: f64, std_dev: f64) -> Result<Self, Error> {
+        if std_dev <= 0.0 || !mean.is_finite() {
+            return Err(Error::InvalidParameter);
+        }
+
+        Ok(Self { mean, std_dev })
+    }
+
+    /// Maximum-likelihood fit, using the population standard deviation
+    pub fn fit<S: Statistics + ?Sized>(data: &S) -> Result<Self, Error> {
+        Self::new(data.try_population_mean()?, data.try_population_std_dev()?)
+    }
+}
+
+impl ContinuousDistribution for Normal {
+    fn pdf(&self, x: f64) -> f64 {
+        special::standard_normal_pdf((x - self.mean) / self.std_dev) / self.std_dev
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        special::standard_normal_cdf((x - self.mean) / self.std_dev)
+    }
+
+    fn inverse_cdf(&self, p: f64) -> f64 {
+        self.mean + self.std_dev * special::standard_normal_inverse_cdf(p)
+    }
+
+    fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    fn variance(&self) -> f64 {
+        self.std_dev.powi(2)
+    }
+}
+
+/// Lognormal distribution, where the logarithm of the variable is normally distributed
+#[derive(Debug, Clone, Copy)]
+pub struct LogNormal {
+    /// Mean of the logarithm of the variable
+    pub mu: f64,
+    /// Standard deviation of the logarithm of the variable
+    pub sigma: f64,
+}
+
+impl LogNormal {
+    pub fn new(mu: f64, sigma: f64) -> Result<Self, Error> {
+        if sigma <= 0.0 || !mu.is_finite() {
+            return Err(Error::InvalidParameter);
+        }
+
+        Ok(Self { mu, sigma })
+    }
+
+    /// Maximum-likelihood fit to positive values, e.g. gross returns `1 + r` or prices
+    pub fn fit<S: Statistics + ?Sized>(data: &S) -> Result<Self, Error> {
+        let values = data.try_values(1)?;
+
+        if values.iter().any(|v| *v <= 0.0) {
+            return Err(Error::InvalidData);
+        }
+
+        let logs = values.iter().map(|v| v.ln()).collect::<Vec<f64>>();
+
+        Self::new(logs.try_population_mean()?, logs.try_population_std_dev()?)
+    }
+}
+
+impl ContinuousDistribution for LogNormal {
+    fn pdf(&self, x: f64) -> f64 {
+        if x <= 0.0 {
+            return 0.0;
+        }
+
+        special::standard_normal_pdf((x.ln() - self.mu) / self.sigma) / (x * self.sigma)
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        if x <= 0.0 {
+            return 0.0;
+        }
+
+        special::standard_normal_cdf((x.ln() - self.mu) / self.sigma)
+    }
+
+    fn inverse_cdf(&self, p: f64) -> f64 {
+        (self.mu + self.sigma * special::standard_normal_inverse_cdf(p)).exp()
+    }
+
+    fn mean(&self) -> f64 {
+        (self.mu + self.sigma.powi(2) / 2.0).exp()
+    }
+
+    fn variance(&self) -> f64 {
+        (self.sigma.powi(2).exp() - 1.0) * (2.0 * self.mu + self.sigma.powi(2)).exp()
+    }
+}
+
+/// Student's t-distribution with location and scale
+#[derive(Debug, Clone, Copy)]
+pub struct StudentT {
+    pub location: f64,
+    pub scale: f64,
+    pub degrees_of_freedom: f64,
+}
+
+impl StudentT {
+    pub fn new(location: f64, scale: f64, degrees_of_freedom: f64) -> Result<Self, Error> {
+        if scale <= 0.0 || degrees_of_freedom <= 0.0 || !location.is_finite() {
+            return Err(Error::InvalidParameter);
+        }
+
+        Ok(Self {
+            location,
+            scale,
+            degrees_of_freedom,
+        })
+    }
+
+    /// Standard t-distribution with zero location and unit scale
+    pub fn standard(degrees_of_freedom: f64) -> Result<Self, Error> {
+        Self::new(0.0, 1.0, degrees_of_freedom)
+    }
+
+    /// Maximum-likelihood fit of the location, scale and degrees of freedom.
+    ///
+    /// The location and scale are fitted by expectation-maximization for a given
+    /// degrees of freedom, which is chosen by maximizing the profile likelihood.
+    pub fn fit<S: Statistics + ?Sized>(data: &S) -> Result<Self, Error> {
+        let values = data.try_values(2)?;
+
+        if data.try_population_variance()? == 0.0 {
+            return Err(StatsError::DegenerateVariance.into());
+        }
+
+        let fit_for = |nu: f64| -> Result<Self, Error> {
+            let mut location = data.try_population_mean()?;
+            let mut scale = data.try_population_std_dev()?;
+
+            for _ in 0..200 {
+                let weights = values
+                    .iter()
+                    .map(|v| (nu + 1.0) / (nu + ((v - location) / scale).powi(2)))
+                    .collect::<Vec<f64>>();
+
+                let weight_sum = weights.iter().sum::<f64>();
+                let next_location = values
+                    .iter()
+                    .zip(weights.iter())
+                    .map(|(v, w)| v * w)
+                    .sum::<f64>()
+                    / weight_sum;
+                let next_scale = (values
+                    .iter()
+                    .zip(weights.iter())
+                    .map(|(v, w)| w * (v - next_location).powi(2))
+                    .sum::<f64>()
+                    / values.len() as f64)
+                    .sqrt();
+
+                let converged = (next_location - location).abs() < 1e-12 * scale
+                    && (next_scale - scale).abs() < 1e-12 * scale;
+
+                location = next_location;
+                scale = next_scale;
+
+                if converged {
+                    break;
+                }
+            }
+
+            Self::new(location, scale, nu)
+        };
+
+        // Golden-section search for the degrees of freedom over a log scale.
+        let log_likelihood = |log_nu: f64| {
+            fit_for(log_nu.exp())
+                .map(|t| t.log_likelihood(values))
+                .unwrap_or(f64::NEG_INFINITY)
+        };
+
+        let ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+        let (mut a, mut b) = (0.5_f64.ln(), 200.0_f64.ln());
+        let mut c = b - ratio * (b - a);
+        let mut d = a + ratio * (b - a);
+        let (mut fc, mut fd) = (log_likelihood(c), log_likelihood(d));
+
+        while b - a > 1e-6 {
+            if fc > fd {
+                b = d;
+                d = c;
+                fd = fc;
+                c = b - ratio * (b - a);
+                fc = log_likelihood(c);
+            } else {
+                a = c;
+                c = d;
+                fc = fd;
+                d = a + ratio * (b - a);
+                fd = log_likelihood(d);
+            }
+        }
+
+        fit_for(((a + b) / 2.0).exp())
+    }
+}
+
+impl ContinuousDistribution for StudentT {
+    fn pdf(&self, x: f64) -> f64 {
+        let nu = self.degrees_of_freedom;
+        let z = (x - self.location) / self.scale;
+
+        (special::ln_gamma((nu + 1.0) / 2.0)
+            - special::ln_gamma(nu / 2.0)
+            - 0.5 * (nu * std::f64::consts::PI).ln()
+            - (nu + 1.0) / 2.0 * (1.0 + z * z / nu).ln())
+        .exp()
+            / self.scale
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        let nu = self.degrees_of_freedom;
+        let z = (x - self.location) / self.scale;
+        let tail = 0.5 * special::beta_inc(nu / 2.0, 0.5, nu / (nu + z * z));
+
+        if z > 0.0 {
+            1.0 - tail
+        } else {
+            tail
+        }
+    }
+
+    fn inverse_cdf(&self, p: f64) -> f64 {
+        if p <= 0.0 {
+            return f64::NEG_INFINITY;
+        }
+
+        if p >= 1.0 {
+            return f64::INFINITY;
+        }
+
+        let standard = Self {
+            location: 0.0,
+            scale: 1.0,
+            ..*self
+        };
+
+        self.location + self.scale * invert_cdf(|t| standard.cdf(t), p, -1.0, 1.0)
+    }
+
+    /// Mean of the distribution, undefined (NaN) for one or fewer degrees of freedom
+    fn mean(&self) -> f64 {
+        if self.degrees_of_freedom > 1.0 {
+            self.location
+        } else {
+            f64::NAN
+        }
+    }
+
+    /// Variance of the distribution, infinite for two or fewer degrees of freedom
+    fn variance(&self) -> f64 {
+        let nu = self.degrees_of_freedom;
+
+        if nu > 2.0 {
+            self.scale.powi(2) * nu / (nu - 2.0)
+        } else if nu > 1.0 {
+            f64::INFINITY
+        } else {
+            f64::NAN
+        }
+    }
+}
+
+/// Chi-square distribution
+#[derive(Debug, Clone, Copy)]
+pub struct ChiSquared {
+    pub degrees_of_freedom: f64,
+}
+
+impl ChiSquared {
+    pub fn new(degrees_of_freedom: f64) -> Result<Self, Error> {
+        if degrees_of_freedom <= 0.0 {
+            return Err(Error::InvalidParameter);
+        }
+
+        Ok(Self { degrees_of_freedom })
+    }
+
+    /// Maximum-likelihood fit of the degrees of freedom to positive values
+    pub fn fit<S: Statistics + ?Sized>(data: &S) -> Result<Self, Error> {
+        let values = data.try_values(1)?;
+
+        if values.iter().any(|v| *v <= 0.0) {
+            return Err(Error::InvalidData);
+        }
+
+        // The likelihood is maximized where digamma(k / 2) = mean(ln x) - ln 2.
+        let target = values.iter().map(|v| v.ln()).sum::<f64>() / values.len() as f64
+            - std::f64::consts::LN_2;
+
+        let k = optimize::bisection(|k| special::digamma(k / 2.0) - target, 1e-6, 1e6)
+            .ok_or(Error::InvalidData)?;
+
+        Self::new(k)
+    }
+}
+
+impl ContinuousDistribution for ChiSquared {
+    fn pdf(&self, x: f64) -> f64 {
+        if x <= 0.0 {
+            return 0.0;
+        }
+
+        let k = self.degrees_of_freedom / 2.0;
+
+        ((k - 1.0) * x.ln() - x / 2.0 - k * std::f64::consts::LN_2 - special::ln_gamma(k)).exp()
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        special::gamma_p(self.degrees_of_freedom / 2.0, x / 2.0)
+    }
+
+    fn inverse_cdf(&self, p: f64) -> f64 {
+        if p <= 0.0 {
+            return 0.0;
+        }
+
+        if p >= 1.0 {
+            return f64::INFINITY;
+        }
+
+        invert_cdf(|x| self.cdf(x), p, 0.0, self.degrees_of_freedom.max(1.0))
+    }
+
+    fn mean(&self) -> f64 {
+        self.degrees_of_freedom
+    }
+
+    fn variance(&self) -> f64 {
+        2.0 * self.degrees_of_freedom
+    }
+}
+
+/// Continuous uniform distribution over `[lower, upper]`
+#[derive(Debug, Clone, Copy)]
+pub struct Uniform {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl Uniform {
+    pub fn new(lower: f64, upper: f64) -> Result<Self, Error> {
+        if lower >= upper || !lower.is_finite() || !upper.is_finite() {
+            return Err(Error::InvalidParameter);
+        }
+
+        Ok(Self { lower, upper })
+    }
+
+    /// Maximum-likelihood fit, the minimum and maximum of the data
+    pub fn fit<S: Statistics + ?Sized>(data: &S) -> Result<Self, Error> {
+        Self::new(data.try_min()?, data.try_max()?)
+    }
+}
+
+impl ContinuousDistribution for Uniform {
+    fn pdf(&self, x: f64) -> f64 {
+        if x < self.lower || x > self.upper {
+            return 0.0;
+        }
+
+        1.0 / (self.upper - self.lower)
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        ((x - self.lower) / (self.upper - self.lower)).clamp(0.0, 1.0)
+    }
+
+    fn inverse_cdf(&self, p: f64) -> f64 {
+        self.lower + p * (self.upper - self.lower)
+    }
+
+    fn mean(&self) -> f64 {
+        (self.lower + self.upper) / 2.0
+    }
+
+    fn variance(&self) -> f64 {
+        (self.upper - self.lower).powi(2) / 12.0
+    }
+}
+
+/// Exponential distribution
+#[derive(Debug, Clone, Copy)]
+pub struct Exponential {
+    pub rate: f64,
+}
+
+impl Exponential {
+    pub fn new(rate: f64) -> Result<Self, Error> {
+        if rate <= 0.0 {
+            return Err(Error::InvalidParameter);
+        }
+
+        Ok(Self { rate })
+    }
+
+    /// Maximum-likelihood fit to non-negative values, the reciprocal of the mean
+    ///
+    /// The values must not all be zero, which would give an infinite rate.
+    pub fn fit<S: Statistics + ?Sized>(data: &S) -> Result<Self, Error> {
+        let mean = data.try_population_mean()?;
+
+        if data.try_min()? < 0.0 || mean <= 0.0 {
+            return Err(Error::InvalidData);
+        }
+
+        Self::new(1.0 / mean)
+    }
+}
+
+impl ContinuousDistribution for Exponential {
+    fn pdf(&self, x: f64) -> f64 {
+        if x < 0.0 {
+            return 0.0;
+        }
+
+        self.rate * (-self.rate * x).exp()
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        if x < 0.0 {
+            return 0.0;
+        }
+
+        1.0 - (-self.rate * x).exp()
+    }
+
+    fn inverse_cdf(&self, p: f64) -> f64 {
+        -(1.0 - p).ln() / self.rate
+    }
+
+    fn mean(&self) -> f64 {
+        1.0 / self.rate
+    }
+
+    fn variance(&self) -> f64 {
+        1.0 / self.rate.powi(2)
+    }
+}
+
+/// A discrete probability distribution over the non-negative integers
+pub trait DiscreteDistribution {
+    /// Probability mass function
+    fn pmf(&self, k: u64) -> f64;
+
+    /// Cumulative distribution function, `P(X <= k)`
+    fn cdf(&self, k: u64) -> f64;
+
+    /// Mean of the distribution
+    fn mean(&self) -> f64;
+
+    /// Variance of the distribution
+    fn variance(&self) -> f64;
+
+    /// Standard deviation of the distribution
+    fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Smallest `k` in the support such that `P(X <= k) >= p`
+    fn quantile(&self, p: f64) -> Result<u64, Error> {
+        if !(0.0..=1.0).contains(&p) {
+            return Err(Error::InvalidProbability(p));
+        }
+
+        // The zero quantile is the start of the support, the first `k` with any mass.
+        let covers = |k: u64| {
+            if p == 0.0 {
+                self.cdf(k) > 0.0
+            } else {
+                self.cdf(k) >= p
+            }
+        };
+
+        // Double the upper bound until it covers the quantile, then binary search.
+        let mut lower = 0;
+        let mut upper = 1;
+
+        while !covers(upper) {
+            lower = upper;
+            upper = upper.checked_mul(2).ok_or(Error::InvalidProbability(p))?;
+        }
+
+        if covers(lower) {
+            return Ok(lower);
+        }
+
+        while upper - lower > 1 {
+            let mid = lower + (upper - lower) / 2;
+
+            if covers(mid) {
+                upper = mid;
+            } else {
+                lower = mid;
+            }
+        }
+
+        Ok(upper)
+    }
+
+    /// Draw a random value by inverse transform sampling
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        // A uniform draw is in [0, 1), so the quantile always exists.
+        self.quantile(rng.gen::<f64>()).unwrap_or(0)
+    }
+
+    /// Draw `n` random values
+    fn sample_n<R: Rng + ?Sized>(&self, rng: &mut R, n: usize) -> Vec<u64> {
+        (0..n).map(|_| self.sample(rng)).collect()
+    }
+}
+
+/// Validate that a probability of success is between 0 and 1
+fn check_probability(p: f64) -> Result<(), Error> {
+    if !(0.0..=1.0).contains(&p) {
+        return Err(Error::InvalidProbability(p));
+    }
+
+    Ok(())
+}
+
+/// Binomial distribution of the number of successes in `n` independent trials
+#[derive(Debug, Clone, Copy)]
+pub struct Binomial {
+    /// Number of trials
+    pub trials: u64,
+    /// Probability of success in each trial
+    pub probability: f64,
+}
+
+impl Binomial {
+    pub fn new(trials: u64, probability: f64) -> Result<Self, Error> {
+        check_probability(probability)?;
+
+        Ok(Self {
+            trials,
+            probability,
+        })
+    }
+}
+
+impl DiscreteDistribution for Binomial {
+    fn pmf(&self, k: u64) -> f64 {
+        let (n, p) = (self.trials, self.probability);
+
+        if k > n {
+            return 0.0;
+        }
+
+        // Degenerate distributions put all mass on zero or n successes.
+        if p == 0.0 {
+            return if k == 0 { 1.0 } else { 0.0 };
+        }
+
+        if p == 1.0 {
+            return if k == n { 1.0 } else { 0.0 };
+        }
+
+        (combinatorics::ln_combinations(n, k) + k as f64 * p.ln() + (n - k) as f64 * (1.0 - p).ln())
+            .exp()
+    }
+
+    fn cdf(&self, k: u64) -> f64 {
+        let (n, p) = (self.trials, self.probability);
+
+        if k >= n {
+            return 1.0;
+        }
+
+        special::beta_inc((n - k) as f64, k as f64 + 1.0, 1.0 - p)
+    }
+
+    fn mean(&self) -> f64 {
+        self.trials as f64 * self.probability
+    }
+
+    fn variance(&self) -> f64 {
+        self.trials as f64 * self.probability * (1.0 - self.probability)
+    }
+}
+
+/// Bernoulli distribution of a single trial, a binomial distribution with one trial
+#[derive(Debug, Clone, Copy)]
+pub struct Bernoulli {
+    /// Probability of success
+    pub probability: f64,
+}
+
+impl Bernoulli {
+    pub fn new(probability: f64) -> Result<Self, Error> {
+        check_probability(probability)?;
+
+        Ok(Self { probability })
+    }
+}
+
+impl DiscreteDistribution for Bernoulli {
+    fn pmf(&self, k: u64) -> f64 {
+        match k {
+            0 => 1.0 - self.probability,
+            1 => self.probability,
+            _ => 0.0,
+        }
+    }
+
+    fn cdf(&self, k: u64) -> f64 {
+        match k {
+            0 => 1.0 - self.probability,
+            _ => 1.0,
+        }
+    }
+
+    fn mean(&self) -> f64 {
+        self.probability
+    }
+
+    fn variance(&self) -> f64 {
+        self.probability * (1.0 - self.probability)
+    }
+}
+
+/// Poisson distribution of the number of events in a fixed interval
+#[derive(Debug, Clone, Copy)]
+pub struct Poisson {
+    /// Expected number of events in the interval
+    pub lambda: f64,
+}
+
+impl Poisson {
+    pub fn new(lambda: f64) -> Result<Self, Error> {
+        if !(lambda >= 0.0 && lambda.is_finite()) {
+            return Err(Error::InvalidParameter);
+        }
+
+        Ok(Self { lambda })
+    }
+}
+
+impl DiscreteDistribution for Poisson {
+    fn pmf(&self, k: u64) -> f64 {
+        // With no events expected, all of the mass is at zero.
+        if self.lambda == 0.0 {
+            return if k == 0 { 1.0 } else { 0.0 };
+        }
+
+        (k as f64 * self.lambda.ln() - self.lambda - combinatorics::ln_factorial(k)).exp()
+    }
+
+    fn cdf(&self, k: u64) -> f64 {
+        special::gamma_q(k as f64 + 1.0, self.lambda)
+    }
+
+    fn mean(&self) -> f64 {
+        self.lambda
+    }
+
+    fn variance(&self) -> f64 {
+        self.lambda
+    }
+}
+
+/// Geometric distribution of the number of trials up to and including the first success
+#[derive(Debug, Clone, Copy)]
+pub struct Geometric {
+    /// Probability of success in each trial
+    pub probability: f64,
+}
+
+impl Geometric {
+    pub fn new(probability: f64) -> Result<Self, Error> {
+        if probability <= 0.0 || probability > 1.0 {
+            return Err(Error::InvalidProbability(probability));
+        }
+
+        Ok(Self { probability })
+    }
+}
+
+impl DiscreteDistribution for Geometric {
+    fn pmf(&self, k: u64) -> f64 {
+        if k == 0 {
+            return 0.0;
+        }
+
+        (1.0 - self.probability).powf(k as f64 - 1.0) * self.probability
+    }
+
+    fn cdf(&self, k: u64) -> f64 {
+        1.0 - (1.0 - self.probability).powf(k as f64)
+    }
+
+    fn mean(&self) -> f64 {
+        1.0 / self.probability
+    }
+
+    fn variance(&self) -> f64 {
+        (1.0 - self.probability) / self.probability.powi(2)
+    }
+}
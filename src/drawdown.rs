@@ -0,0 +1,185 @@
+use chrono::{DateTime, Utc};
+
+use crate::stats::{Statistics, StatsError};
+use crate::stock_data::StockData;
+
+/// Calculate the wealth index of compounded returns, starting from a value of one
+pub fn wealth_index(returns: &[f64]) -> Vec<f64> {
+    returns
+        .iter()
+        .scan(1.0, |wealth, r| {
+            *wealth *= 1.0 + r;
+            Some(*wealth)
+        })
+        .collect()
+}
+
+/// Calculate the drawdown of each period from the running peak of a wealth index, as a
+/// non-positive fraction
+///
+/// The running peak starts at one, the value of the wealth index before the first period.
+pub fn drawdown_series(wealth_index: &[f64]) -> Vec<f64> {
+    let mut peak: f64 = 1.0;
+
+    wealth_index
+        .iter()
+        .map(|wealth| {
+            peak = peak.max(*wealth);
+            wealth / peak - 1.0
+        })
+        .collect()
+}
+
+/// A single drawdown, from a peak of the wealth index until it recovers to that peak
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawdownEpisode {
+    /// Period of the peak, or `None` if the drawdown is from the initial value of the index
+    pub peak: Option<usize>,
+    /// Period of the lowest value of the index
+    pub trough: usize,
+    /// First period the index is back at its peak, or `None` if it has not recovered
+    pub recovery: Option<usize>,
+    /// Largest decline from the peak, as a positive fraction
+    pub depth: f64,
+    /// Number of periods below the peak, up to the recovery or the end of the series
+    pub duration: usize,
+    pub peak_date: Option<DateTime<Utc>>,
+    pub trough_date: Option<DateTime<Utc>>,
+    pub recovery_date: Option<DateTime<Utc>>,
+}
+
+impl DrawdownEpisode {
+    /// Number of periods from the peak to the trough
+    pub fn decline(&self) -> usize {
+        self.trough - self.peak.map_or(0, |peak| peak + 1) + 1
+    }
+
+    /// Number of periods from the trough to the recovery, if the index has recovered
+    pub fn recovery_periods(&self) -> Option<usize> {
+        self.recovery.map(|recovery| recovery - self.trough)
+    }
+}
+
+/// Drawdown analysis of a series of periodic returns
+#[derive(Debug, Clone)]
+pub struct Drawdowns {
+    /// Compounded value of one unit invested at the start of the series
+    pub wealth_index: Vec<f64>,
+    /// Decline of each period from the running peak, as a non-positive fraction
+    pub drawdowns: Vec<f64>,
+    /// Drawdown episodes in chronological order
+    pub episodes: Vec<DrawdownEpisode>,
+}
+
+impl Drawdowns {
+    /// Analyze the drawdowns of a series of returns
+    pub fn from_returns<S: Statistics + ?Sized>(returns: &S) -> Result<Self, StatsError> {
+        Self::analyze(returns.try_values(1)?, None)
+    }
+
+    /// Analyze the drawdowns of a stock's returns, including dividends, with the dates of each
+    /// episode
+    ///
+    /// Bars must be in chronological order, as for `StockData::returns`.
+    pub fn from_stock_data(stock_data: &StockData) -> Result<Self, StatsError> {
+        let dates: Vec<DateTime<Utc>> = stock_data.data.iter().map(|d| d.date).collect();
+
+        Self::analyze(stock_data.returns().try_values(1)?, Some(&dates))
+    }
+
+    fn analyze(returns: &[f64], dates: Option<&[DateTime<Utc>]>) -> Result<Self, StatsError> {
+        if returns.iter().any(|r| *r <= -1.0) {
+            return Err(StatsError::InvalidDomain);
+        }
+
+        let wealth_index = wealth_index(returns);
+        let drawdowns = drawdown_series(&wealth_index);
+        let date = |period: Option<usize>| dates.zip(period).map(|(dates, i)| dates[i]);
+
+        let mut episodes = vec![];
+        let mut peak = None;
+        let mut current: Option<DrawdownEpisode> = None;
+
+        for (i, drawdown) in drawdowns.iter().enumerate() {
+            if *drawdown < 0.0 {
+                let episode = current.get_or_insert(DrawdownEpisode {
+                    peak,
+                    trough: i,
+                    recovery: None,
+                    depth: 0.0,
+                    duration: 0,
+                    peak_date: date(peak),
+                    trough_date: None,
+                    recovery_date: None,
+                });
+
+                if -drawdown > episode.depth {
+                    episode.depth = -drawdown;
+                    episode.trough = i;
+                }
+
+                episode.duration += 1;
+            } else {
+                if let Some(mut episode) = current.take() {
+                    episode.recovery = Some(i);
+                    episode.recovery_date = date(Some(i));
+                    episodes.push(episode);
+                }
+
+                peak = Some(i);
+            }
+        }
+
+        episodes.extend(current);
+
+        for episode in episodes.iter_mut() {
+            episode.trough_date = date(Some(episode.trough));
+        }
+
+        Ok(Self {
+            wealth_index,
+            drawdowns,
+            episodes,
+        })
+    }
+
+    /// Return the deepest drawdown, or `None` if the index never declined
+    pub fn max_drawdown(&self) -> Option<&DrawdownEpisode> {
+        self.episodes
+            .iter()
+            .max_by(|a, b| a.depth.total_cmp(&b.depth))
+    }
+
+    /// Calculate the average depth of the drawdown episodes, or zero if there are none
+    pub fn average_drawdown(&self) -> f64 {
+        if self.episodes.is_empty() {
+            return 0.0;
+        }
+
+        self.episodes.iter().map(|e| e.depth).sum::<f64>() / self.episodes.len() as f64
+    }
+
+    /// Return the longest time spent below a peak, in periods
+    pub fn max_duration(&self) -> usize {
+        self.episodes.iter().map(|e| e.duration).max().unwrap_or(0)
+    }
+
+    /// Calculate the average time spent below a peak, in periods, or zero if there are no
+    /// drawdowns
+    pub fn average_duration(&self) -> f64 {
+        if self.episodes.is_empty() {
+            return 0.0;
+        }
+
+        self.episodes.iter().map(|e| e.duration).sum::<usize>() as f64 / self.episodes.len() as f64
+    }
+
+    /// Return the `n` deepest drawdown episodes, deepest first
+    pub fn top(&self, n: usize) -> Vec<&DrawdownEpisode> {
+        let mut episodes: Vec<&DrawdownEpisode> = self.episodes.iter().collect();
+        episodes.sort_by(|a, b| b.depth.total_cmp(&a.depth));
+        episodes.truncate(n);
+
+        episodes
+    }
+}
@@ -0,0 +1,257 @@
+use std::f64::consts::PI;
+
+use crate::quantile::{self, QuantileMethod, QuantileStatistics};
+use crate::stats::{Statistics, StatsError};
+
+/// Rule for choosing the bins of a histogram
+#[derive(Debug, Clone, Copy)]
+pub enum BinRule {
+    /// Bins of a fixed width, starting at the minimum value
+    FixedWidth(f64),
+    /// A fixed number of equal-width bins
+    Count(usize),
+    /// `ceil(log2(n)) + 1` bins, suited to roughly normal data
+    Sturges,
+    /// Bin width of `2 * IQR / n^(1/3)`, robust to heavy tails
+    FreedmanDiaconis,
+}
+
+/// Largest number of bins a histogram may have
+pub const MAX_BINS: usize = 1_000_000;
+
+/// Counts of values falling into contiguous equal-width bins
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    /// Bin edges, one more than the number of bins
+    pub edges: Vec<f64>,
+    /// Number of values in each bin; the last bin includes its upper edge
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    /// Bin a set of values according to a bin rule
+    ///
+    /// Values must be finite, and rules giving more than `MAX_BINS` bins are rejected.
+    pub fn new(values: &[f64], rule: BinRule) -> Result<Self, StatsError> {
+        let stats = values.try_moments(1)?;
+        let (min, max) = (stats.min(), stats.max());
+
+        if !min.is_finite() || !max.is_finite() {
+            return Err(StatsError::InvalidDomain);
+        }
+
+        let range = max - min;
+
+        let bins = match rule {
+            BinRule::FixedWidth(width) => {
+                if !(width.is_finite() && width > 0.0) {
+                    return Err(StatsError::InvalidDomain);
+                }
+                (range / width).floor() + 1.0
+            }
+            BinRule::Count(count) => {
+                if count == 0 {
+                    return Err(StatsError::InvalidDomain);
+                }
+                count as f64
+            }
+            BinRule::Sturges => sturges_bins(values.len()) as f64,
+            BinRule::FreedmanDiaconis => {
+                let width = 2.0 * values.interquartile_range(QuantileMethod::Type7)?
+                    / (values.len() as f64).cbrt();
+
+                // Fall back to Sturges when most values are identical.
+                if width > 0.0 {
+                    (range / width).ceil().max(1.0)
+                } else {
+                    sturges_bins(values.len()) as f64
+                }
+            }
+        };
+
+        if bins > MAX_BINS as f64 {
+            return Err(StatsError::InvalidDomain);
+        }
+
+        let bins = bins as usize;
+
+        let width = match rule {
+            BinRule::FixedWidth(width) => width,
+            _ if range == 0.0 => 1.0,
+            _ => range / bins as f64,
+        };
+
+        let edges = (0..=bins).map(|i| min + i as f64 * width).collect();
+        let mut counts = vec![0; bins];
+
+        for value in values {
+            let bin = (((value - min) / width).floor() as usize).min(bins - 1);
+            counts[bin] += 1;
+        }
+
+        Ok(Self { edges, counts })
+    }
+
+    /// Width of each bin
+    pub fn bin_width(&self) -> f64 {
+        self.edges[1] - self.edges[0]
+    }
+
+    /// Midpoint of each bin
+    pub fn centers(&self) -> Vec<f64> {
+        self.edges.windows(2).map(|w| (w[0] + w[1]) / 2.0).collect()
+    }
+
+    /// Total number of values binned
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Proportion of values in each bin
+    pub fn frequencies(&self) -> Vec<f64> {
+        let total = self.total() as f64;
+
+        self.counts.iter().map(|c| *c as f64 / total).collect()
+    }
+
+    /// Density of each bin, such that the histogram integrates to one
+    pub fn densities(&self) -> Vec<f64> {
+        let width = self.bin_width();
+
+        self.frequencies().iter().map(|f| f / width).collect()
+    }
+}
+
+/// Number of bins given by Sturges' rule
+fn sturges_bins(count: usize) -> usize {
+    (count as f64).log2().ceil() as usize + 1
+}
+
+/// Empirical cumulative distribution function (ECDF) of a sample
+#[derive(Debug, Clone)]
+pub struct EmpiricalCdf {
+    sorted: Vec<f64>,
+}
+
+impl EmpiricalCdf {
+    /// Build the empirical distribution of a set of values
+    pub fn new(values: &[f64]) -> Result<Self, StatsError> {
+        let mut sorted = values.try_values(1)?.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        Ok(Self { sorted })
+    }
+
+    /// Proportion of values less than or equal to `x`
+    pub fn cdf(&self, x: f64) -> f64 {
+        self.sorted.partition_point(|v| *v <= x) as f64 / self.sorted.len() as f64
+    }
+
+    /// Smallest value whose cumulative probability is at least `probability`
+    pub fn inverse(&self, probability: f64) -> Result<f64, StatsError> {
+        quantile::quantile_sorted(&self.sorted, probability, QuantileMethod::Type1)
+    }
+
+    /// Sorted values of the sample
+    pub fn values(&self) -> &[f64] {
+        &self.sorted
+    }
+}
+
+/// Rule for choosing the bandwidth of a kernel density estimate
+#[derive(Debug, Clone, Copy)]
+pub enum Bandwidth {
+    /// Silverman's rule of thumb, `0.9 * min(s, IQR / 1.34) * n^(-1/5)`
+    Silverman,
+    /// Scott's rule, `1.06 * s * n^(-1/5)`
+    Scott,
+    /// A fixed bandwidth
+    Fixed(f64),
+}
+
+/// Gaussian kernel density estimate (KDE) of a sample
+#[derive(Debug, Clone)]
+pub struct KernelDensity {
+    values: Vec<f64>,
+    /// Standard deviation of the Gaussian kernel
+    pub bandwidth: f64,
+}
+
+impl KernelDensity {
+    /// Build a density estimate of a set of values with a bandwidth rule
+    pub fn new(values: &[f64], bandwidth: Bandwidth) -> Result<Self, StatsError> {
+        let n = values.try_values(2)?.len() as f64;
+
+        let bandwidth = match bandwidth {
+            Bandwidth::Fixed(h) => h,
+            Bandwidth::Scott => 1.06 * values.try_sample_std_dev()? * n.powf(-0.2),
+            Bandwidth::Silverman => {
+                let std_dev = values.try_sample_std_dev()?;
+                let iqr = values.interquartile_range(QuantileMethod::Type7)? / 1.34;
+                let spread = if iqr > 0.0 { std_dev.min(iqr) } else { std_dev };
+
+                0.9 * spread * n.powf(-0.2)
+            }
+        };
+
+        if bandwidth <= 0.0 || !bandwidth.is_finite() {
+            return Err(StatsError::DegenerateVariance);
+        }
+
+        Ok(Self {
+            values: values.to_vec(),
+            bandwidth,
+        })
+    }
+
+    /// Estimated probability density at `x`
+    pub fn pdf(&self, x: f64) -> f64 {
+        let h = self.bandwidth;
+
+        self.values
+            .iter()
+            .map(|v| (-0.5 * ((x - v) / h).powi(2)).exp())
+            .sum::<f64>()
+            / (self.values.len() as f64 * h * (2.0 * PI).sqrt())
+    }
+
+    /// Estimated density over an evenly spaced grid of `points` spanning three bandwidths past the data
+    pub fn evaluate(&self, points: usize) -> Vec<(f64, f64)> {
+        let stats = self.values.as_slice().try_moments(1);
+        let (min, max) = match stats {
+            Ok(stats) => (stats.min(), stats.max()),
+            Err(_) => return vec![],
+        };
+
+        let lower = min - 3.0 * self.bandwidth;
+        let upper = max + 3.0 * self.bandwidth;
+        let step = (upper - lower) / (points.max(2) - 1) as f64;
+
+        (0..points)
+            .map(|i| {
+                let x = lower + i as f64 * step;
+                (x, self.pdf(x))
+            })
+            .collect()
+    }
+}
+
+/// Histograms, empirical distribution functions and density estimates of a distribution
+pub trait EmpiricalStatistics: Statistics {
+    /// Bin the values into a histogram
+    fn histogram(&self, rule: BinRule) -> Result<Histogram, StatsError> {
+        Histogram::new(self.values(), rule)
+    }
+
+    /// Build the empirical cumulative distribution function
+    fn ecdf(&self) -> Result<EmpiricalCdf, StatsError> {
+        EmpiricalCdf::new(self.values())
+    }
+
+    /// Build a Gaussian kernel density estimate
+    fn kernel_density(&self, bandwidth: Bandwidth) -> Result<KernelDensity, StatsError> {
+        KernelDensity::new(self.values(), bandwidth)
+    }
+}
+
+impl<T: Statistics + ?Sized> EmpiricalStatistics for T {}
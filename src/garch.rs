@@ -0,0 +1,388 @@
+use std::f64::consts::PI;
+
+use crate::optimize;
+use crate::special;
+use crate::stats::{Statistics, StatsError};
+
+/// Error type for GARCH models
+#[derive(Debug)]
+pub enum Error {
+    /// The series is empty, contains NaN, or has no variance
+    Stats(StatsError),
+    /// The series is too short to fit the model
+    InsufficientData { required: usize, actual: usize },
+    /// The optimizer did not find parameters with a finite likelihood
+    NoSolution,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<StatsError> for Error {
+    fn from(e: StatsError) -> Self {
+        Error::Stats(e)
+    }
+}
+
+/// Minimum number of observations required to fit a GARCH model
+pub const MIN_OBSERVATIONS: usize = 20;
+
+/// Variance equation of a GARCH(1, 1)-family model, with errors `e_t = r_t - mu` and
+/// standardized errors `z_t = e_t / sigma_t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VarianceModel {
+    /// `sigma^2_t = omega + alpha * e^2_{t-1} + beta * sigma^2_{t-1}` (Bollerslev, 1986)
+    Garch,
+    /// `sigma^2_t = omega + (alpha + gamma * I(e_{t-1} < 0)) * e^2_{t-1} + beta * sigma^2_{t-1}`
+    /// (Glosten, Jagannathan and Runkle, 1993)
+    GjrGarch,
+    /// `ln sigma^2_t = omega + alpha * (|z_{t-1}| - E|z|) + gamma * z_{t-1} + beta * ln sigma^2_{t-1}`
+    /// (Nelson, 1991)
+    Egarch,
+}
+
+/// Distribution of the standardized innovations `z_t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Innovation {
+    #[default]
+    Normal,
+    /// Student's t scaled to unit variance, with estimated degrees of freedom
+    StudentT,
+}
+
+/// A GARCH(1, 1)-family volatility model fitted by maximum likelihood
+#[derive(Debug, Clone)]
+pub struct Garch {
+    pub model: VarianceModel,
+    pub innovation: Innovation,
+    /// Constant mean of the returns
+    pub mu: f64,
+    pub omega: f64,
+    pub alpha: f64,
+    pub beta: f64,
+    /// Asymmetry (leverage) coefficient, zero for a symmetric GARCH model
+    pub gamma: f64,
+    /// Degrees of freedom of Student's t innovations
+    pub degrees_of_freedom: Option<f64>,
+    pub log_likelihood: f64,
+    pub aic: f64,
+    pub bic: f64,
+    /// Fitted conditional variance of each observation
+    pub conditional_variance: Vec<f64>,
+    /// Error of the last observation, used for forecasting
+    last_error: f64,
+}
+
+/// Parameters of the variance equation, on the scale of the data
+#[derive(Debug, Clone, Copy)]
+struct Parameters {
+    mu: f64,
+    omega: f64,
+    alpha: f64,
+    beta: f64,
+    gamma: f64,
+    degrees_of_freedom: Option<f64>,
+}
+
+impl Parameters {
+    /// Unpack an optimizer vector of `[mu, omega, alpha, beta, gamma?, nu?]`
+    fn unpack(x: &[f64], model: VarianceModel, innovation: Innovation) -> Self {
+        let gamma = match model {
+            VarianceModel::Garch => 0.0,
+            _ => x[4],
+        };
+
+        Self {
+            mu: x[0],
+            omega: x[1],
+            alpha: x[2],
+            beta: x[3],
+            gamma,
+            degrees_of_freedom: match innovation {
+                Innovation::Normal => None,
+                Innovation::StudentT => x.last().copied(),
+            },
+        }
+    }
+
+    /// Whether the parameters give a positive, covariance-stationary variance process
+    fn is_valid(&self, model: VarianceModel) -> bool {
+        let innovation_valid = self
+            .degrees_of_freedom
+            .is_none_or(|nu| nu > 2.0 && nu <= 500.0);
+
+        let variance_valid = match model {
+            VarianceModel::Garch => {
+                self.omega > 0.0
+                    && self.alpha >= 0.0
+                    && self.beta >= 0.0
+                    && self.alpha + self.beta < 1.0
+            }
+            VarianceModel::GjrGarch => {
+                self.omega > 0.0
+                    && self.alpha >= 0.0
+                    && self.alpha + self.gamma >= 0.0
+                    && self.beta >= 0.0
+                    && self.alpha + self.gamma / 2.0 + self.beta < 1.0
+            }
+            VarianceModel::Egarch => self.beta.abs() < 1.0,
+        };
+
+        innovation_valid && variance_valid
+    }
+
+    /// Expected absolute value of a standardized innovation, `E|z|`
+    fn expected_absolute_innovation(&self) -> f64 {
+        match self.degrees_of_freedom {
+            None => (2.0 / PI).sqrt(),
+            Some(nu) => {
+                2.0 * (nu - 2.0).sqrt()
+                    * (special::ln_gamma((nu + 1.0) / 2.0) - special::ln_gamma(nu / 2.0)).exp()
+                    / ((nu - 1.0) * PI.sqrt())
+            }
+        }
+    }
+
+    /// Conditional variance of the next period given the current error and variance
+    fn next_variance(&self, model: VarianceModel, error: f64, variance: f64) -> f64 {
+        match model {
+            VarianceModel::Garch => self.omega + self.alpha * error * error + self.beta * variance,
+            VarianceModel::GjrGarch => {
+                let leverage = if error < 0.0 { self.gamma } else { 0.0 };
+
+                self.omega + (self.alpha + leverage) * error * error + self.beta * variance
+            }
+            VarianceModel::Egarch => {
+                let z = error / variance.sqrt();
+
+                (self.omega
+                    + self.alpha * (z.abs() - self.expected_absolute_innovation())
+                    + self.gamma * z
+                    + self.beta * variance.ln())
+                .exp()
+            }
+        }
+    }
+
+    /// Calculate the conditional variances, starting from the sample variance of the errors
+    fn conditional_variance(&self, model: VarianceModel, values: &[f64]) -> Vec<f64> {
+        let initial =
+            values.iter().map(|v| (v - self.mu).powi(2)).sum::<f64>() / values.len() as f64;
+
+        let mut variances = Vec::with_capacity(values.len());
+        variances.push(initial);
+
+        for t in 1..values.len() {
+            let next = self.next_variance(model, values[t - 1] - self.mu, variances[t - 1]);
+            variances.push(next);
+        }
+
+        variances
+    }
+
+    /// Log-likelihood of the values given their conditional variances
+    fn log_likelihood(&self, values: &[f64], variances: &[f64]) -> f64 {
+        let constant = match self.degrees_of_freedom {
+            None => -0.5 * (2.0 * PI).ln(),
+            Some(nu) => {
+                special::ln_gamma((nu + 1.0) / 2.0)
+                    - special::ln_gamma(nu / 2.0)
+                    - 0.5 * (PI * (nu - 2.0)).ln()
+            }
+        };
+
+        values
+            .iter()
+            .zip(variances)
+            .map(|(v, variance)| {
+                let squared = (v - self.mu).powi(2) / variance;
+
+                let kernel = match self.degrees_of_freedom {
+                    None => 0.5 * squared,
+                    Some(nu) => (nu + 1.0) / 2.0 * (1.0 + squared / (nu - 2.0)).ln(),
+                };
+
+                constant - 0.5 * variance.ln() - kernel
+            })
+            .sum()
+    }
+}
+
+impl Garch {
+    /// Fit a GARCH(1, 1)-family model with a constant mean by maximum likelihood
+    ///
+    /// The returns are standardized before optimizing so that the parameters have similar
+    /// scales, and the variance recursion starts from the sample variance.
+    pub fn fit<S: Statistics + ?Sized>(
+        data: &S,
+        model: VarianceModel,
+        innovation: Innovation,
+    ) -> Result<Self, Error> {
+        let values = data.try_values(1)?;
+
+        if values.len() < MIN_OBSERVATIONS {
+            return Err(Error::InsufficientData {
+                required: MIN_OBSERVATIONS,
+                actual: values.len(),
+            });
+        }
+
+        let scale = data.try_nonzero_std_dev()?;
+        let standardized: Vec<f64> = values.iter().map(|v| v / scale).collect();
+        let mean = standardized.iter().sum::<f64>() / standardized.len() as f64;
+
+        let mut initial = match model {
+            VarianceModel::Garch => vec![mean, 0.05, 0.05, 0.9],
+            VarianceModel::GjrGarch => vec![mean, 0.05, 0.03, 0.9, 0.05],
+            VarianceModel::Egarch => vec![mean, -0.01, 0.1, 0.95, -0.05],
+        };
+
+        if innovation == Innovation::StudentT {
+            initial.push(8.0);
+        }
+
+        let negative_log_likelihood = |x: &[f64]| {
+            let parameters = Parameters::unpack(x, model, innovation);
+
+            if !parameters.is_valid(model) {
+                return f64::INFINITY;
+            }
+
+            let variances = parameters.conditional_variance(model, &standardized);
+
+            if variances.iter().any(|v| !(v.is_finite() && *v > 0.0)) {
+                return f64::INFINITY;
+            }
+
+            -parameters.log_likelihood(&standardized, &variances)
+        };
+
+        // Restart the simplex from the best point to avoid premature convergence.
+        let mut estimate = initial;
+
+        for _ in 0..3 {
+            estimate = optimize::nelder_mead(
+                negative_log_likelihood,
+                &estimate,
+                1e-10,
+                1000 * estimate.len(),
+            );
+        }
+
+        if !negative_log_likelihood(&estimate).is_finite() {
+            return Err(Error::NoSolution);
+        }
+
+        // Convert the parameters back to the scale of the data.
+        let mut parameters = Parameters::unpack(&estimate, model, innovation);
+        parameters.mu *= scale;
+        parameters.omega = match model {
+            VarianceModel::Egarch => {
+                parameters.omega + (1.0 - parameters.beta) * scale.powi(2).ln()
+            }
+            _ => parameters.omega * scale.powi(2),
+        };
+
+        let conditional_variance = parameters.conditional_variance(model, values);
+        let log_likelihood = parameters.log_likelihood(values, &conditional_variance);
+
+        let observations = values.len() as f64;
+        let parameter_count = estimate.len() as f64;
+
+        Ok(Self {
+            model,
+            innovation,
+            mu: parameters.mu,
+            omega: parameters.omega,
+            alpha: parameters.alpha,
+            beta: parameters.beta,
+            gamma: parameters.gamma,
+            degrees_of_freedom: parameters.degrees_of_freedom,
+            log_likelihood,
+            aic: -2.0 * log_likelihood + 2.0 * parameter_count,
+            bic: -2.0 * log_likelihood + observations.ln() * parameter_count,
+            last_error: values[values.len() - 1] - parameters.mu,
+            conditional_variance,
+        })
+    }
+
+    fn parameters(&self) -> Parameters {
+        Parameters {
+            mu: self.mu,
+            omega: self.omega,
+            alpha: self.alpha,
+            beta: self.beta,
+            gamma: self.gamma,
+            degrees_of_freedom: self.degrees_of_freedom,
+        }
+    }
+
+    /// Fitted conditional volatility (standard deviation) of each observation
+    pub fn conditional_volatility(&self) -> Vec<f64> {
+        self.conditional_variance.iter().map(|v| v.sqrt()).collect()
+    }
+
+    /// Persistence of shocks to the variance
+    ///
+    /// For GJR-GARCH this assumes symmetric innovations, and for EGARCH it is the persistence
+    /// of the log variance.
+    pub fn persistence(&self) -> f64 {
+        match self.model {
+            VarianceModel::Garch => self.alpha + self.beta,
+            VarianceModel::GjrGarch => self.alpha + self.gamma / 2.0 + self.beta,
+            VarianceModel::Egarch => self.beta,
+        }
+    }
+
+    /// Long-run variance that forecasts revert to
+    pub fn unconditional_variance(&self) -> f64 {
+        match self.model {
+            VarianceModel::Egarch => (self.omega / (1.0 - self.beta)).exp(),
+            _ => self.omega / (1.0 - self.persistence()),
+        }
+    }
+
+    /// Forecast the conditional variance for each of the next `horizon` periods
+    ///
+    /// EGARCH forecasts beyond one period iterate the expected log variance, which ignores
+    /// the convexity adjustment and slightly understates the variance.
+    pub fn forecast_variance(&self, horizon: usize) -> Vec<f64> {
+        let parameters = self.parameters();
+        let last_variance = self.conditional_variance[self.conditional_variance.len() - 1];
+        let mut next = parameters.next_variance(self.model, self.last_error, last_variance);
+        let mut forecasts = Vec::with_capacity(horizon);
+
+        for _ in 0..horizon {
+            forecasts.push(next);
+
+            next = match self.model {
+                VarianceModel::Egarch => (self.omega + self.beta * next.ln()).exp(),
+                _ => {
+                    let long_run = self.unconditional_variance();
+                    long_run + self.persistence() * (next - long_run)
+                }
+            };
+        }
+
+        forecasts
+    }
+
+    /// Forecast the volatility of each of the next `horizon` periods, annualized by the number
+    /// of periods per year
+    pub fn forecast_volatility(&self, horizon: usize, periods_per_year: f64) -> Vec<f64> {
+        self.forecast_variance(horizon)
+            .into_iter()
+            .map(|v| (v * periods_per_year).sqrt())
+            .collect()
+    }
+
+    /// Forecast the volatility of the cumulative return over the next `horizon` periods
+    pub fn forecast_horizon_volatility(&self, horizon: usize) -> f64 {
+        self.forecast_variance(horizon).iter().sum::<f64>().sqrt()
+    }
+}
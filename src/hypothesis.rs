@@ -0,0 +1,451 @@
+use crate::distributions::{self, ChiSquared, ContinuousDistribution, StudentT};
+use crate::special;
+use crate::stats::{RunningStats, Statistics, StatsError};
+
+/// Error type for hypothesis tests
+#[derive(Debug)]
+pub enum Error {
+    /// The data is empty, too short, contains NaN, or has no variance
+    Stats(StatsError),
+    /// The reference distribution could not be constructed
+    Distribution(distributions::Error),
+    /// The test is not defined for this many observations
+    UnsupportedSampleSize(usize),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<StatsError> for Error {
+    fn from(e: StatsError) -> Self {
+        Error::Stats(e)
+    }
+}
+
+impl From<distributions::Error> for Error {
+    fn from(e: distributions::Error) -> Self {
+        Error::Distribution(e)
+    }
+}
+
+/// Largest sample supported by the Royston approximation to the Shapiro-Wilk test
+pub const SHAPIRO_WILK_MAX_SAMPLE: usize = 5000;
+
+/// The alternative hypothesis of a test
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Alternative {
+    /// The parameter differs from the hypothesized value
+    #[default]
+    TwoSided,
+    /// The parameter is less than the hypothesized value
+    Less,
+    /// The parameter is greater than the hypothesized value
+    Greater,
+}
+
+impl Alternative {
+    /// Convert the cumulative probability of the test statistic into a p-value
+    fn p_value(&self, cdf: f64) -> f64 {
+        match self {
+            Alternative::TwoSided => (2.0 * cdf.min(1.0 - cdf)).min(1.0),
+            Alternative::Less => cdf,
+            Alternative::Greater => 1.0 - cdf,
+        }
+    }
+}
+
+/// The outcome of a hypothesis test
+#[derive(Debug, Clone, Copy)]
+pub struct TestResult {
+    pub statistic: f64,
+    pub p_value: f64,
+    /// Degrees of freedom of the reference distribution (numerator degrees of freedom for an F-test)
+    pub degrees_of_freedom: Option<f64>,
+    /// Denominator degrees of freedom for an F-test
+    pub denominator_degrees_of_freedom: Option<f64>,
+}
+
+impl TestResult {
+    pub(crate) fn new(statistic: f64, p_value: f64, degrees_of_freedom: Option<f64>) -> Self {
+        Self {
+            statistic,
+            p_value: p_value.clamp(0.0, 1.0),
+            degrees_of_freedom,
+            denominator_degrees_of_freedom: None,
+        }
+    }
+
+    /// Whether the null hypothesis is rejected at the significance level `alpha`
+    pub fn reject(&self, alpha: f64) -> bool {
+        self.p_value < alpha
+    }
+}
+
+/// Run a t-test of a statistic against a Student's t distribution
+fn t_test_result(
+    statistic: f64,
+    degrees_of_freedom: f64,
+    alternative: Alternative,
+) -> Result<TestResult, Error> {
+    let cdf = StudentT::standard(degrees_of_freedom)?.cdf(statistic);
+
+    Ok(TestResult::new(
+        statistic,
+        alternative.p_value(cdf),
+        Some(degrees_of_freedom),
+    ))
+}
+
+/// Calculate the standard error of the mean, failing if the values have no variance
+fn standard_error(moments: &RunningStats) -> Result<f64, StatsError> {
+    let standard_error = (moments.sample_variance() / moments.count() as f64).sqrt();
+
+    if standard_error == 0.0 {
+        return Err(StatsError::DegenerateVariance);
+    }
+
+    Ok(standard_error)
+}
+
+/// Survival function of the Kolmogorov distribution, `P(K > lambda)`
+fn kolmogorov_survival(lambda: f64) -> f64 {
+    let a = -2.0 * lambda * lambda;
+    let mut sign = 2.0;
+    let mut sum = 0.0;
+    let mut previous = 0.0;
+
+    for j in 1..=100 {
+        let term = sign * (a * (j * j) as f64).exp();
+        sum += term;
+
+        if term.abs() <= 1e-3 * previous || term.abs() <= 1e-8 * sum {
+            return sum.clamp(0.0, 1.0);
+        }
+
+        sign = -sign;
+        previous = term.abs();
+    }
+
+    // The series fails to converge for very small lambda, where the p-value is one.
+    1.0
+}
+
+/// Kolmogorov-Smirnov p-value for a statistic `d` with `n` effective observations (Stephens, 1970)
+fn kolmogorov_smirnov_p_value(d: f64, n: f64) -> f64 {
+    let root_n = n.sqrt();
+
+    kolmogorov_survival((root_n + 0.12 + 0.11 / root_n) * d)
+}
+
+/// Evaluate a polynomial with coefficients in increasing order of power
+fn polynomial(coefficients: &[f64], x: f64) -> f64 {
+    coefficients.iter().rev().fold(0.0, |acc, c| acc * x + c)
+}
+
+/// Hypothesis tests on sample data
+pub trait HypothesisTests: Statistics {
+    /// One-sample t-test that the mean equals `mean`
+    fn t_test(&self, mean: f64, alternative: Alternative) -> Result<TestResult, Error> {
+        let moments = self.try_moments(2)?;
+        let statistic = (moments.mean() - mean) / standard_error(&moments)?;
+
+        t_test_result(statistic, (moments.count() - 1) as f64, alternative)
+    }
+
+    /// Two-sample Student's t-test that the means are equal, assuming equal variances
+    fn two_sample_t_test<S: Statistics + ?Sized>(
+        &self,
+        other: &S,
+        alternative: Alternative,
+    ) -> Result<TestResult, Error> {
+        let x = self.try_moments(2)?;
+        let y = other.try_moments(2)?;
+
+        let (n_x, n_y) = (x.count() as f64, y.count() as f64);
+        let degrees_of_freedom = n_x + n_y - 2.0;
+        let pooled_variance = ((n_x - 1.0) * x.sample_variance()
+            + (n_y - 1.0) * y.sample_variance())
+            / degrees_of_freedom;
+        let standard_error = (pooled_variance * (1.0 / n_x + 1.0 / n_y)).sqrt();
+
+        if standard_error == 0.0 {
+            return Err(StatsError::DegenerateVariance.into());
+        }
+
+        t_test_result(
+            (x.mean() - y.mean()) / standard_error,
+            degrees_of_freedom,
+            alternative,
+        )
+    }
+
+    /// Welch's t-test that the means are equal, without assuming equal variances
+    fn welch_t_test<S: Statistics + ?Sized>(
+        &self,
+        other: &S,
+        alternative: Alternative,
+    ) -> Result<TestResult, Error> {
+        let x = self.try_moments(2)?;
+        let y = other.try_moments(2)?;
+
+        let (n_x, n_y) = (x.count() as f64, y.count() as f64);
+        let variance_x = x.sample_variance() / n_x;
+        let variance_y = y.sample_variance() / n_y;
+        let standard_error = (variance_x + variance_y).sqrt();
+
+        if standard_error == 0.0 {
+            return Err(StatsError::DegenerateVariance.into());
+        }
+
+        // Welch-Satterthwaite approximation to the degrees of freedom.
+        let degrees_of_freedom = (variance_x + variance_y).powi(2)
+            / (variance_x.powi(2) / (n_x - 1.0) + variance_y.powi(2) / (n_y - 1.0));
+
+        t_test_result(
+            (x.mean() - y.mean()) / standard_error,
+            degrees_of_freedom,
+            alternative,
+        )
+    }
+
+    /// Paired t-test that the mean difference between matched observations is zero
+    fn paired_t_test<S: Statistics + ?Sized>(
+        &self,
+        other: &S,
+        alternative: Alternative,
+    ) -> Result<TestResult, Error> {
+        let x = self.try_values(2)?;
+        let y = other.try_values(2)?;
+
+        if x.len() != y.len() {
+            return Err(StatsError::LengthMismatch {
+                expected: x.len(),
+                actual: y.len(),
+            }
+            .into());
+        }
+
+        let differences: Vec<f64> = x.iter().zip(y).map(|(a, b)| a - b).collect();
+
+        differences.t_test(0.0, alternative)
+    }
+
+    /// F-test that the variances of two samples are equal
+    fn f_test<S: Statistics + ?Sized>(
+        &self,
+        other: &S,
+        alternative: Alternative,
+    ) -> Result<TestResult, Error> {
+        let x = self.try_moments(2)?;
+        let y = other.try_moments(2)?;
+
+        if y.sample_variance() == 0.0 {
+            return Err(StatsError::DegenerateVariance.into());
+        }
+
+        let statistic = x.sample_variance() / y.sample_variance();
+        let d1 = (x.count() - 1) as f64;
+        let d2 = (y.count() - 1) as f64;
+        let cdf = special::beta_inc(d1 / 2.0, d2 / 2.0, d1 * statistic / (d1 * statistic + d2));
+
+        Ok(TestResult {
+            denominator_degrees_of_freedom: Some(d2),
+            ..TestResult::new(statistic, alternative.p_value(cdf), Some(d1))
+        })
+    }
+
+    /// Jarque-Bera test that the sample skewness and excess kurtosis match a normal distribution
+    fn jarque_bera(&self) -> Result<TestResult, Error> {
+        let moments = self.try_moments(2)?;
+        let values = self.values();
+        let n = moments.count() as f64;
+        let mean = moments.mean();
+
+        let central_moment = |k: i32| values.iter().map(|v| (v - mean).powi(k)).sum::<f64>() / n;
+        let m2 = central_moment(2);
+
+        if m2 == 0.0 {
+            return Err(StatsError::DegenerateVariance.into());
+        }
+
+        let skewness = central_moment(3) / m2.powf(1.5);
+        let excess_kurtosis = central_moment(4) / m2.powi(2) - 3.0;
+        let statistic = n / 6.0 * (skewness.powi(2) + excess_kurtosis.powi(2) / 4.0);
+        let p_value = 1.0 - ChiSquared::new(2.0)?.cdf(statistic);
+
+        Ok(TestResult::new(statistic, p_value, Some(2.0)))
+    }
+
+    /// Shapiro-Wilk test of normality, using Royston's (1995) approximation for 3 to 5000 values
+    fn shapiro_wilk(&self) -> Result<TestResult, Error> {
+        let mut x = self.try_values(3)?.to_vec();
+        let n = x.len();
+
+        if n > SHAPIRO_WILK_MAX_SAMPLE {
+            return Err(Error::UnsupportedSampleSize(n));
+        }
+
+        x.sort_by(|a, b| a.total_cmp(b));
+
+        let mean = x.iter().sum::<f64>() / n as f64;
+        let sum_squares: f64 = x.iter().map(|v| (v - mean).powi(2)).sum();
+
+        if sum_squares == 0.0 {
+            return Err(StatsError::DegenerateVariance.into());
+        }
+
+        // Coefficients of the order statistics, antisymmetric about the median.
+        let coefficients = if n == 3 {
+            vec![-0.5f64.sqrt(), 0.0, 0.5f64.sqrt()]
+        } else {
+            let m: Vec<f64> = (1..=n)
+                .map(|i| {
+                    special::standard_normal_inverse_cdf((i as f64 - 0.375) / (n as f64 + 0.25))
+                })
+                .collect();
+            let mm: f64 = m.iter().map(|v| v * v).sum();
+            let u = 1.0 / (n as f64).sqrt();
+
+            let a_n = m[n - 1] / mm.sqrt()
+                + polynomial(
+                    &[0.0, 0.221157, -0.147981, -2.071190, 4.434685, -2.706056],
+                    u,
+                );
+
+            let (tails, phi) = if n > 5 {
+                let a_n1 = m[n - 2] / mm.sqrt()
+                    + polynomial(
+                        &[0.0, 0.042981, -0.293762, -1.752461, 5.682633, -3.582633],
+                        u,
+                    );
+                let phi = (mm - 2.0 * m[n - 1].powi(2) - 2.0 * m[n - 2].powi(2))
+                    / (1.0 - 2.0 * a_n.powi(2) - 2.0 * a_n1.powi(2));
+
+                (vec![a_n, a_n1], phi)
+            } else {
+                let phi = (mm - 2.0 * m[n - 1].powi(2)) / (1.0 - 2.0 * a_n.powi(2));
+
+                (vec![a_n], phi)
+            };
+
+            let mut a: Vec<f64> = m.iter().map(|v| v / phi.sqrt()).collect();
+
+            for (i, tail) in tails.iter().enumerate() {
+                a[n - 1 - i] = *tail;
+                a[i] = -tail;
+            }
+
+            a
+        };
+
+        let statistic = (coefficients
+            .iter()
+            .zip(&x)
+            .map(|(a, v)| a * v)
+            .sum::<f64>()
+            .powi(2)
+            / sum_squares)
+            .min(1.0);
+
+        let p_value = if n == 3 {
+            6.0 / std::f64::consts::PI * (statistic.sqrt().asin() - 0.75f64.sqrt().asin())
+        } else {
+            let y = (1.0 - statistic).ln();
+            let n = n as f64;
+
+            let (z, mu, sigma) = if n <= 11.0 {
+                let gamma = polynomial(&[-2.273, 0.459], n);
+
+                if y >= gamma {
+                    return Ok(TestResult::new(statistic, 0.0, None));
+                }
+
+                (
+                    -(gamma - y).ln(),
+                    polynomial(&[0.5440, -0.39978, 0.025054, -0.0006714], n),
+                    polynomial(&[1.3822, -0.77857, 0.062767, -0.0020322], n).exp(),
+                )
+            } else {
+                let ln_n = n.ln();
+
+                (
+                    y,
+                    polynomial(&[-1.5861, -0.31082, -0.083751, 0.0038915], ln_n),
+                    polynomial(&[-0.4803, -0.082676, 0.0030302], ln_n).exp(),
+                )
+            };
+
+            1.0 - special::standard_normal_cdf((z - mu) / sigma)
+        };
+
+        Ok(TestResult::new(statistic, p_value, None))
+    }
+
+    /// One-sample Kolmogorov-Smirnov test that the values are drawn from `distribution`
+    fn kolmogorov_smirnov<D: ContinuousDistribution>(
+        &self,
+        distribution: &D,
+    ) -> Result<TestResult, Error> {
+        let mut x = self.try_values(1)?.to_vec();
+        x.sort_by(|a, b| a.total_cmp(b));
+
+        let n = x.len() as f64;
+        let statistic = x
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let cdf = distribution.cdf(*v);
+                (cdf - i as f64 / n).max((i + 1) as f64 / n - cdf)
+            })
+            .fold(0.0, f64::max);
+
+        Ok(TestResult::new(
+            statistic,
+            kolmogorov_smirnov_p_value(statistic, n),
+            None,
+        ))
+    }
+
+    /// Two-sample Kolmogorov-Smirnov test that both samples are drawn from the same distribution
+    fn two_sample_kolmogorov_smirnov<S: Statistics + ?Sized>(
+        &self,
+        other: &S,
+    ) -> Result<TestResult, Error> {
+        let mut x = self.try_values(1)?.to_vec();
+        let mut y = other.try_values(1)?.to_vec();
+        x.sort_by(|a, b| a.total_cmp(b));
+        y.sort_by(|a, b| a.total_cmp(b));
+
+        let (n_x, n_y) = (x.len() as f64, y.len() as f64);
+        let (mut i, mut j) = (0, 0);
+        let mut statistic: f64 = 0.0;
+
+        // Step through both empirical distribution functions, moving past ties together.
+        while i < x.len() && j < y.len() {
+            let v = x[i].min(y[j]);
+
+            while i < x.len() && x[i] <= v {
+                i += 1;
+            }
+
+            while j < y.len() && y[j] <= v {
+                j += 1;
+            }
+
+            statistic = statistic.max((i as f64 / n_x - j as f64 / n_y).abs());
+        }
+
+        Ok(TestResult::new(
+            statistic,
+            kolmogorov_smirnov_p_value(statistic, n_x * n_y / (n_x + n_y)),
+            None,
+        ))
+    }
+}
+
+impl<T: Statistics + ?Sized> HypothesisTests for T {}
@@ -0,0 +1,309 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::distributions::{self, ChiSquared, ContinuousDistribution, StudentT};
+use crate::quantile::{self, QuantileMethod};
+use crate::special;
+use crate::stats::{Statistics, StatsError};
+use crate::time_series::TimeSeriesStatistics;
+
+/// Error type for confidence intervals and bootstrap resampling
+#[derive(Debug)]
+pub enum Error {
+    /// The data is empty, too short, contains NaN, or has no variance
+    Stats(StatsError),
+    /// The reference distribution could not be constructed
+    Distribution(distributions::Error),
+    /// Confidence levels must be strictly between 0 and 1
+    InvalidConfidence(f64),
+    /// Mean block lengths must be at least one
+    InvalidBlockLength(f64),
+    /// At least one resample is required
+    NoResamples,
+    /// The statistic was undefined (NaN) for every resample
+    UndefinedStatistic,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<StatsError> for Error {
+    fn from(e: StatsError) -> Self {
+        Error::Stats(e)
+    }
+}
+
+impl From<distributions::Error> for Error {
+    fn from(e: distributions::Error) -> Self {
+        Error::Distribution(e)
+    }
+}
+
+/// A point estimate with a two-sided confidence interval
+#[derive(Debug, Clone, Copy)]
+pub struct ConfidenceInterval {
+    pub estimate: f64,
+    pub lower: f64,
+    pub upper: f64,
+    /// Confidence level, e.g. 0.95
+    pub confidence: f64,
+}
+
+impl ConfidenceInterval {
+    /// Width of the interval
+    pub fn width(&self) -> f64 {
+        self.upper - self.lower
+    }
+
+    /// Whether the interval contains a value
+    pub fn contains(&self, value: f64) -> bool {
+        (self.lower..=self.upper).contains(&value)
+    }
+}
+
+/// Ensure a confidence level is strictly between 0 and 1
+fn check_confidence(confidence: f64) -> Result<(), Error> {
+    if !(confidence > 0.0 && confidence < 1.0) {
+        return Err(Error::InvalidConfidence(confidence));
+    }
+
+    Ok(())
+}
+
+/// Parametric confidence intervals for sample statistics
+pub trait ConfidenceIntervals: Statistics {
+    /// Confidence interval for the mean, using Student's t distribution
+    fn mean_confidence_interval(&self, confidence: f64) -> Result<ConfidenceInterval, Error> {
+        check_confidence(confidence)?;
+
+        let moments = self.try_moments(2)?;
+        let n = moments.count() as f64;
+        let t = StudentT::standard(n - 1.0)?.inverse_cdf((1.0 + confidence) / 2.0);
+        let margin = t * moments.sample_std_dev() / n.sqrt();
+
+        Ok(ConfidenceInterval {
+            estimate: moments.mean(),
+            lower: moments.mean() - margin,
+            upper: moments.mean() + margin,
+            confidence,
+        })
+    }
+
+    /// Confidence interval for the variance, assuming normally distributed values
+    fn variance_confidence_interval(&self, confidence: f64) -> Result<ConfidenceInterval, Error> {
+        check_confidence(confidence)?;
+
+        let moments = self.try_moments(2)?;
+        let degrees_of_freedom = (moments.count() - 1) as f64;
+        let chi_squared = ChiSquared::new(degrees_of_freedom)?;
+        let sum_squares = degrees_of_freedom * moments.sample_variance();
+
+        Ok(ConfidenceInterval {
+            estimate: moments.sample_variance(),
+            lower: sum_squares / chi_squared.inverse_cdf((1.0 + confidence) / 2.0),
+            upper: sum_squares / chi_squared.inverse_cdf((1.0 - confidence) / 2.0),
+            confidence,
+        })
+    }
+
+    /// Confidence interval for the Sharpe Ratio using the asymptotic standard error of Lo (2002)
+    ///
+    /// The ratio is annualized over `periods_per_year` periods (1 for no annualization) with Lo's
+    /// scale factor, which corrects the usual `sqrt(q)` rule for serial correlation using the
+    /// first `lags` autocorrelations. A `lags` of 0 assumes independent returns.
+    fn sharpe_ratio_confidence_interval(
+        &self,
+        risk_free_rate: f64,
+        periods_per_year: usize,
+        lags: usize,
+        confidence: f64,
+    ) -> Result<ConfidenceInterval, Error> {
+        check_confidence(confidence)?;
+
+        let sharpe_ratio = self.try_sharpe_ratio(risk_free_rate)?;
+        let n = self.count() as f64;
+        let q = periods_per_year.max(1);
+
+        let max_lag = lags.min(q - 1).min(self.count() - 1);
+        let autocorrelations = if max_lag > 0 {
+            self.try_autocorrelations(max_lag)
+                .map_err(|_| StatsError::DegenerateVariance)?
+        } else {
+            vec![]
+        };
+
+        let weighted_autocorrelations: f64 = autocorrelations
+            .iter()
+            .enumerate()
+            .map(|(i, rho)| (q - i - 1) as f64 * rho)
+            .sum();
+        let scale_squared = q as f64 + 2.0 * weighted_autocorrelations;
+
+        if scale_squared <= 0.0 {
+            return Err(StatsError::DegenerateVariance.into());
+        }
+
+        let scale = q as f64 / scale_squared.sqrt();
+        let standard_error = ((1.0 + sharpe_ratio.powi(2) / 2.0) / n).sqrt();
+        let margin =
+            special::standard_normal_inverse_cdf((1.0 + confidence) / 2.0) * standard_error * scale;
+        let estimate = sharpe_ratio * scale;
+
+        Ok(ConfidenceInterval {
+            estimate,
+            lower: estimate - margin,
+            upper: estimate + margin,
+            confidence,
+        })
+    }
+}
+
+impl<T: Statistics + ?Sized> ConfidenceIntervals for T {}
+
+/// How observations are drawn when resampling
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resampling {
+    /// Draw observations independently with replacement
+    Iid,
+    /// Stationary block bootstrap of Politis and Romano (1994), drawing blocks of geometrically
+    /// distributed length to preserve serial dependence
+    StationaryBlock { mean_block_length: f64 },
+}
+
+/// Seeded bootstrap resampling engine
+#[derive(Debug, Clone, Copy)]
+pub struct Bootstrap {
+    /// Number of resamples to draw
+    pub resamples: usize,
+    pub resampling: Resampling,
+    /// Seed for the random number generator, so results are reproducible
+    pub seed: u64,
+}
+
+impl Bootstrap {
+    /// Create an iid bootstrap
+    pub fn new(resamples: usize, seed: u64) -> Self {
+        Self {
+            resamples,
+            resampling: Resampling::Iid,
+            seed,
+        }
+    }
+
+    /// Resample with the stationary block bootstrap, for autocorrelated values
+    pub fn with_stationary_blocks(mut self, mean_block_length: f64) -> Self {
+        self.resampling = Resampling::StationaryBlock { mean_block_length };
+        self
+    }
+
+    /// Draw one resample of the values
+    fn resample<R: Rng + ?Sized>(&self, values: &[f64], rng: &mut R, sample: &mut Vec<f64>) {
+        let n = values.len();
+        sample.clear();
+
+        match self.resampling {
+            Resampling::Iid => sample.extend((0..n).map(|_| values[rng.gen_range(0..n)])),
+            Resampling::StationaryBlock { mean_block_length } => {
+                let restart = 1.0 / mean_block_length;
+                let mut index = rng.gen_range(0..n);
+
+                for _ in 0..n {
+                    sample.push(values[index]);
+
+                    index = if rng.gen::<f64>() < restart {
+                        rng.gen_range(0..n)
+                    } else {
+                        (index + 1) % n
+                    };
+                }
+            }
+        }
+    }
+
+    /// Calculate the statistic on each resample of the data
+    pub fn replicates<S, F>(&self, data: &S, statistic: F) -> Result<Vec<f64>, Error>
+    where
+        S: Statistics + ?Sized,
+        F: Fn(&[f64]) -> f64,
+    {
+        if self.resamples == 0 {
+            return Err(Error::NoResamples);
+        }
+
+        if let Resampling::StationaryBlock { mean_block_length } = self.resampling {
+            if mean_block_length.is_nan() || mean_block_length < 1.0 {
+                return Err(Error::InvalidBlockLength(mean_block_length));
+            }
+        }
+
+        let values = data.try_values(1)?;
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut sample = Vec::with_capacity(values.len());
+
+        Ok((0..self.resamples)
+            .map(|_| {
+                self.resample(values, &mut rng, &mut sample);
+                statistic(&sample)
+            })
+            .collect())
+    }
+
+    /// Bootstrap standard error of the statistic
+    pub fn standard_error<S, F>(&self, data: &S, statistic: F) -> Result<f64, Error>
+    where
+        S: Statistics + ?Sized,
+        F: Fn(&[f64]) -> f64,
+    {
+        let replicates = self.replicates(data, statistic)?;
+
+        Ok(replicates.try_sample_std_dev()?)
+    }
+
+    /// Percentile bootstrap confidence interval for the statistic
+    ///
+    /// Resamples on which the statistic is undefined (NaN) are discarded.
+    pub fn percentile_interval<S, F>(
+        &self,
+        data: &S,
+        statistic: F,
+        confidence: f64,
+    ) -> Result<ConfidenceInterval, Error>
+    where
+        S: Statistics + ?Sized,
+        F: Fn(&[f64]) -> f64,
+    {
+        check_confidence(confidence)?;
+
+        let estimate = statistic(data.try_values(1)?);
+        let mut replicates: Vec<f64> = self
+            .replicates(data, &statistic)?
+            .into_iter()
+            .filter(|v| !v.is_nan())
+            .collect();
+
+        if replicates.is_empty() {
+            return Err(Error::UndefinedStatistic);
+        }
+
+        replicates.sort_by(|a, b| a.total_cmp(b));
+
+        Ok(ConfidenceInterval {
+            estimate,
+            lower: quantile::quantile_sorted(
+                &replicates,
+                (1.0 - confidence) / 2.0,
+                QuantileMethod::Type7,
+            )?,
+            upper: quantile::quantile_sorted(
+                &replicates,
+                (1.0 + confidence) / 2.0,
+                QuantileMethod::Type7,
+            )?,
+            confidence,
+        })
+    }
+}
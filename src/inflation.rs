@@ -0,0 +1,155 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::stock_data::{Returns, StockData};
+
+/// Error type for inflation adjustments
+#[derive(Debug)]
+pub enum Error {
+    /// The CPI series has no observations
+    EmptyCpiSeries,
+    /// No CPI observation exists on or before the given date
+    MissingCpiObservation(DateTime<Utc>),
+    /// The return and inflation series have different lengths
+    LengthMismatch,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Convert a nominal rate to a real rate with the Fisher equation
+pub fn real_rate(nominal_rate: f64, inflation_rate: f64) -> f64 {
+    (1.0 + nominal_rate) / (1.0 + inflation_rate) - 1.0
+}
+
+/// Convert a real rate to a nominal rate with the Fisher equation
+pub fn nominal_rate(real_rate: f64, inflation_rate: f64) -> f64 {
+    (1.0 + real_rate) * (1.0 + inflation_rate) - 1.0
+}
+
+/// Convert a series of nominal returns to real returns, given the inflation rate of each period
+pub fn deflate_returns(returns: &Returns, inflation_rates: &[f64]) -> Result<Returns, Error> {
+    if returns.0.len() != inflation_rates.len() {
+        return Err(Error::LengthMismatch);
+    }
+
+    Ok(Returns(
+        returns
+            .0
+            .iter()
+            .zip(inflation_rates.iter())
+            .map(|(r, i)| real_rate(*r, *i))
+            .collect(),
+    ))
+}
+
+/// A single observation of the consumer price index
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CpiObservation {
+    pub date: DateTime<Utc>,
+    pub value: f64,
+}
+
+/// Consumer price index (CPI) series, in ascending date order
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CpiSeries(pub Vec<CpiObservation>);
+
+impl CpiSeries {
+    /// Serialize the series from a saved JSON file of `{ "date", "value" }` observations
+    pub fn from_file(path: std::path::PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = std::fs::File::open(path)?;
+        let reader = std::io::BufReader::new(file);
+        let mut series: Self = serde_json::from_reader(reader)?;
+
+        series.0.sort_by_key(|o| o.date);
+
+        Ok(series)
+    }
+
+    /// Return the CPI level in effect on a date, i.e. the latest observation on or before it
+    pub fn value_at(&self, date: DateTime<Utc>) -> Result<f64, Error> {
+        if self.0.is_empty() {
+            return Err(Error::EmptyCpiSeries);
+        }
+
+        let index = self.0.partition_point(|o| o.date <= date);
+
+        if index == 0 {
+            return Err(Error::MissingCpiObservation(date));
+        }
+
+        Ok(self.0[index - 1].value)
+    }
+
+    /// Return the CPI level on a date, interpolated geometrically between observations
+    ///
+    /// Each month's inflation is spread evenly over the days until the next observation, so that
+    /// daily returns are not deflated by the whole monthly change on the first day of the month.
+    /// Dates after the last observation take its level.
+    pub fn interpolated_value_at(&self, date: DateTime<Utc>) -> Result<f64, Error> {
+        let value = self.value_at(date)?;
+        let index = self.0.partition_point(|o| o.date <= date);
+
+        let Some(next) = self.0.get(index) else {
+            return Ok(value);
+        };
+
+        let previous = &self.0[index - 1];
+        let elapsed = (date - previous.date).num_seconds() as f64;
+        let period = (next.date - previous.date).num_seconds() as f64;
+
+        Ok(value * (next.value / value).powf(elapsed / period))
+    }
+
+    /// Return the period-over-period inflation rates of the series
+    pub fn inflation_rates(&self) -> Vec<f64> {
+        self.0
+            .windows(2)
+            .map(|w| w[1].value / w[0].value - 1.0)
+            .collect()
+    }
+
+    /// Return the inflation rate between two dates, from the interpolated CPI levels
+    pub fn inflation_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<f64, Error> {
+        Ok(self.interpolated_value_at(end)? / self.interpolated_value_at(start)? - 1.0)
+    }
+
+    /// Deflate the closing prices of a stock to the CPI level of a base date
+    pub fn real_prices(
+        &self,
+        stock_data: &StockData,
+        base_date: DateTime<Utc>,
+    ) -> Result<Vec<f64>, Error> {
+        let base = self.interpolated_value_at(base_date)?;
+
+        stock_data
+            .data
+            .iter()
+            .map(|d| Ok(d.close * base / self.interpolated_value_at(d.date)?))
+            .collect()
+    }
+
+    /// Return the real holding period returns of a stock, aligned with `StockData::returns`
+    pub fn real_returns(&self, stock_data: &StockData) -> Result<Returns, Error> {
+        let mut inflation_rates = vec![];
+
+        for (i, d) in stock_data.data.iter().enumerate() {
+            if i == 0 {
+                inflation_rates.push(0.0);
+            } else {
+                inflation_rates.push(self.inflation_between(stock_data.data[i - 1].date, d.date)?);
+            }
+        }
+
+        deflate_returns(&stock_data.returns(), &inflation_rates)
+    }
+}
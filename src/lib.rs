@@ -1,8 +1,32 @@
+pub mod arima;
 pub mod bond;
+pub mod combinatorics;
+pub mod covariance;
+pub mod dcf;
+pub mod ddm;
+pub mod distributions;
+pub mod drawdown;
+pub mod empirical;
+pub mod garch;
+pub mod hypothesis;
+pub mod inference;
+pub mod inflation;
 // pub mod data;
+pub mod loan;
+pub mod matrix;
 pub mod methods;
+pub mod optimize;
+pub mod performance;
 pub mod portfolio;
+pub mod quantile;
+pub mod realized_volatility;
+pub mod regression;
+pub mod rolling;
+pub mod scenario;
+pub mod special;
 pub mod stats;
 pub mod stock_data;
 #[cfg(test)]
 pub mod tests;
+pub mod time_series;
+pub mod value_at_risk;
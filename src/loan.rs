@@ -0,0 +1,314 @@
+use crate::bond::Frequency;
+use crate::methods::{self, net_present_value, PaymentTiming};
+use crate::optimize;
+
+/// Error type for loan instrument
+#[derive(Debug)]
+pub enum Error {
+    /// Principal must be greater than zero
+    InvalidPrincipal,
+    /// Loan must have at least one payment period
+    InvalidTerm,
+    /// Balloon amortization periods must be at least the loan term
+    InvalidAmortization,
+    /// No rate could be found to equate the payments with the loan proceeds
+    NoSolution,
+    /// Prepayments must be finite and non-negative
+    InvalidPrepayment(f64),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Change of the annual interest rate of an adjustable-rate loan
+#[derive(Debug, Clone)]
+pub struct RateReset {
+    /// Payment period (starting at 1) from which the new rate applies
+    pub period: usize,
+    /// New annual interest rate of the loan
+    pub annual_interest_rate: f64,
+}
+
+/// Repayment structure of a loan
+#[derive(Debug, Clone)]
+pub enum LoanType {
+    /// Level payments that fully amortize the loan over its term
+    FixedRate,
+    /// Interest is paid each period and the principal is repaid at maturity
+    InterestOnly,
+    /// Level payments amortized over a longer schedule, with the remaining balance due at maturity
+    Balloon { amortization_periods: usize },
+    /// Payments are recalculated over the remaining term whenever the rate resets
+    AdjustableRate { rate_resets: Vec<RateReset> },
+}
+
+/// A single row of an amortization schedule
+#[derive(Debug, Clone)]
+pub struct Payment {
+    /// Payment period, starting at 1
+    pub period: usize,
+    /// Periodic interest rate applied in the period
+    pub rate: f64,
+    /// Scheduled payment (interest plus principal)
+    pub payment: f64,
+    /// Interest portion of the payment
+    pub interest: f64,
+    /// Principal portion of the payment
+    pub principal: f64,
+    /// Additional principal prepaid in the period
+    pub prepayment: f64,
+    /// Outstanding balance after the payment
+    pub balance: f64,
+}
+
+impl Payment {
+    /// Total cash paid in the period, including prepayment
+    pub fn total(&self) -> f64 {
+        self.payment + self.prepayment
+    }
+}
+
+/// Payment-by-payment amortization schedule of a loan
+#[derive(Debug, Clone, Default)]
+pub struct AmortizationSchedule {
+    pub payments: Vec<Payment>,
+}
+
+impl AmortizationSchedule {
+    /// Total interest paid over the life of the loan
+    pub fn total_interest(&self) -> f64 {
+        self.payments.iter().map(|p| p.interest).sum()
+    }
+
+    /// Total principal repaid over the life of the loan, including prepayments
+    pub fn total_principal(&self) -> f64 {
+        self.payments
+            .iter()
+            .map(|p| p.principal + p.prepayment)
+            .sum()
+    }
+
+    /// Total cash paid over the life of the loan
+    pub fn total_paid(&self) -> f64 {
+        self.payments.iter().map(|p| p.total()).sum()
+    }
+
+    /// Return a vector of cash flows paid by the borrower
+    pub fn cash_flows(&self) -> Vec<f64> {
+        self.payments.iter().map(|p| p.total()).collect()
+    }
+}
+
+/// Representation of an amortizing loan or mortgage
+#[derive(Debug, Clone)]
+pub struct Loan {
+    /// Amount borrowed
+    pub principal: f64,
+    /// Initial annual interest rate of the loan
+    pub annual_interest_rate: f64,
+    /// Frequency of the payments
+    pub frequency: Frequency,
+    /// Number of payment periods
+    pub periods: usize,
+    /// Repayment structure of the loan
+    pub loan_type: LoanType,
+    /// Extra principal payments as (period, amount)
+    pub prepayments: Vec<(usize, f64)>,
+    /// Upfront fees deducted from the loan proceeds
+    pub fees: f64,
+}
+
+impl Loan {
+    /// Method for originating a loan with principal, annual rate, payment frequency and number of periods
+    pub fn originate(
+        principal: f64,
+        annual_interest_rate: f64,
+        frequency: Frequency,
+        periods: usize,
+        loan_type: LoanType,
+    ) -> Result<Self, Error> {
+        if principal <= 0.0 {
+            return Err(Error::InvalidPrincipal);
+        }
+
+        if periods == 0 {
+            return Err(Error::InvalidTerm);
+        }
+
+        if let LoanType::Balloon {
+            amortization_periods,
+        } = loan_type
+        {
+            if amortization_periods < periods {
+                return Err(Error::InvalidAmortization);
+            }
+        }
+
+        Ok(Self {
+            principal,
+            annual_interest_rate,
+            frequency,
+            periods,
+            loan_type,
+            prepayments: Vec::new(),
+            fees: 0.0,
+        })
+    }
+
+    /// Add an extra principal payment in the given period
+    pub fn with_prepayment(mut self, period: usize, amount: f64) -> Self {
+        self.prepayments.push((period, amount));
+        self
+    }
+
+    /// Add the same extra principal payment to every period
+    pub fn with_recurring_prepayment(mut self, amount: f64) -> Self {
+        for period in 1..=self.periods {
+            self.prepayments.push((period, amount));
+        }
+        self
+    }
+
+    /// Set the upfront fees of the loan, used for the annual percentage rate
+    pub fn with_fees(mut self, fees: f64) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Initial periodic interest rate of the loan
+    pub fn periodic_rate(&self) -> f64 {
+        self.annual_interest_rate / self.frequency.to_f64()
+    }
+
+    /// Periodic interest rate in effect for a payment period
+    pub fn rate_for_period(&self, period: usize) -> f64 {
+        let annual_rate = match &self.loan_type {
+            LoanType::AdjustableRate { rate_resets } => rate_resets
+                .iter()
+                .filter(|r| r.period <= period)
+                .max_by_key(|r| r.period)
+                .map(|r| r.annual_interest_rate)
+                .unwrap_or(self.annual_interest_rate),
+            _ => self.annual_interest_rate,
+        };
+
+        annual_rate / self.frequency.to_f64()
+    }
+
+    /// Initial scheduled payment of the loan
+    pub fn payment(&self) -> f64 {
+        match self.loan_type {
+            LoanType::InterestOnly => self.principal * self.periodic_rate(),
+            LoanType::Balloon {
+                amortization_periods,
+            } => level_payment(self.principal, self.periodic_rate(), amortization_periods),
+            _ => level_payment(self.principal, self.periodic_rate(), self.periods),
+        }
+    }
+
+    /// Total extra principal scheduled for a payment period
+    fn prepayment_for_period(&self, period: usize) -> f64 {
+        self.prepayments
+            .iter()
+            .filter(|(p, _)| *p == period)
+            .map(|(_, amount)| amount)
+            .sum()
+    }
+
+    /// Build the payment-by-payment amortization schedule of the loan
+    pub fn schedule(&self) -> Result<AmortizationSchedule, Error> {
+        if let Some((_, amount)) = self
+            .prepayments
+            .iter()
+            .find(|(_, amount)| !(amount.is_finite() && *amount >= 0.0))
+        {
+            return Err(Error::InvalidPrepayment(*amount));
+        }
+
+        let mut payments = Vec::new();
+        let mut balance = self.principal;
+        let mut scheduled_payment = self.payment();
+        let mut prior_rate = self.periodic_rate();
+
+        for period in 1..=self.periods {
+            if balance <= 1e-9 {
+                break;
+            }
+
+            let rate = self.rate_for_period(period);
+            let interest = balance * rate;
+
+            // Adjustable-rate loans recast the payment over the remaining term on a reset.
+            if let LoanType::AdjustableRate { .. } = self.loan_type {
+                if rate != prior_rate {
+                    scheduled_payment = level_payment(balance, rate, self.periods - period + 1);
+                }
+            }
+            prior_rate = rate;
+
+            let mut principal = match self.loan_type {
+                LoanType::InterestOnly => 0.0,
+                _ => scheduled_payment - interest,
+            };
+
+            // The remaining balance is due at maturity, or once the payment would overpay it.
+            if period == self.periods || principal > balance {
+                principal = balance;
+            }
+
+            let prepayment = self.prepayment_for_period(period).min(balance - principal);
+
+            balance -= principal + prepayment;
+
+            payments.push(Payment {
+                period,
+                rate,
+                payment: interest + principal,
+                interest,
+                principal,
+                prepayment,
+                balance,
+            });
+        }
+
+        Ok(AmortizationSchedule { payments })
+    }
+
+    /// Total interest paid over the life of the loan
+    pub fn total_interest(&self) -> Result<f64, Error> {
+        Ok(self.schedule()?.total_interest())
+    }
+
+    /// Periodic rate that equates the payments with the loan proceeds, net of fees
+    fn internal_periodic_rate(&self) -> Result<f64, Error> {
+        let cash_flows = self.schedule()?.cash_flows();
+        let proceeds = self.principal - self.fees;
+
+        optimize::bisection(
+            |rate| net_present_value(proceeds, cash_flows.clone(), rate),
+            -0.9999,
+            10.0,
+        )
+        .ok_or(Error::NoSolution)
+    }
+
+    /// Annual percentage rate (APR) of the loan, including fees
+    pub fn annual_percentage_rate(&self) -> Result<f64, Error> {
+        Ok(self.internal_periodic_rate()? * self.frequency.to_f64())
+    }
+
+    /// Effective annual percentage rate of the loan, including fees and compounding
+    pub fn effective_apr(&self) -> Result<f64, Error> {
+        Ok((1.0 + self.internal_periodic_rate()?).powf(self.frequency.to_f64()) - 1.0)
+    }
+}
+
+/// Level payment that fully amortizes a balance over a number of periods
+fn level_payment(balance: f64, rate: f64, periods: usize) -> f64 {
+    balance / methods::present_value_annuity(1.0, rate, periods as f64, PaymentTiming::End)
+}
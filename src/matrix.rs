@@ -0,0 +1,190 @@
+/// A dense matrix stored as rows
+pub type Matrix = Vec<Vec<f64>>;
+
+/// Create an `n` by `n` identity matrix
+pub fn identity(n: usize) -> Matrix {
+    (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect()
+}
+
+/// Transpose a matrix
+pub fn transpose(a: &[Vec<f64>]) -> Matrix {
+    let columns = a.first().map_or(0, |row| row.len());
+
+    (0..columns)
+        .map(|j| a.iter().map(|row| row[j]).collect())
+        .collect()
+}
+
+/// Multiply two matrices
+pub fn multiply(a: &[Vec<f64>], b: &[Vec<f64>]) -> Matrix {
+    let columns = b.first().map_or(0, |row| row.len());
+
+    a.iter()
+        .map(|row| {
+            (0..columns)
+                .map(|j| row.iter().zip(b).map(|(x, b_row)| x * b_row[j]).sum())
+                .collect()
+        })
+        .collect()
+}
+
+/// Multiply a matrix by a vector
+pub fn multiply_vector(a: &[Vec<f64>], x: &[f64]) -> Vec<f64> {
+    a.iter()
+        .map(|row| row.iter().zip(x).map(|(a, x)| a * x).sum())
+        .collect()
+}
+
+/// Invert a square matrix by Gauss-Jordan elimination with partial pivoting
+///
+/// Returns `None` if the matrix is singular.
+pub fn inverse(a: &[Vec<f64>]) -> Option<Matrix> {
+    let n = a.len();
+    let mut left = a.to_vec();
+    let mut right = identity(n);
+
+    // Pivots smaller than this, relative to the largest entry, are treated as zero.
+    let scale = a.iter().flatten().fold(0.0, |max: f64, v| max.max(v.abs()));
+    let tolerance = scale * n as f64 * f64::EPSILON;
+
+    for column in 0..n {
+        let pivot =
+            (column..n).max_by(|i, j| left[*i][column].abs().total_cmp(&left[*j][column].abs()))?;
+
+        if left[pivot][column].abs() <= tolerance {
+            return None;
+        }
+
+        left.swap(column, pivot);
+        right.swap(column, pivot);
+
+        let divisor = left[column][column];
+        left[column].iter_mut().for_each(|v| *v /= divisor);
+        right[column].iter_mut().for_each(|v| *v /= divisor);
+
+        for row in 0..n {
+            if row == column {
+                continue;
+            }
+
+            let factor = left[row][column];
+
+            if factor != 0.0 {
+                for k in 0..n {
+                    left[row][k] -= factor * left[column][k];
+                    right[row][k] -= factor * right[column][k];
+                }
+            }
+        }
+    }
+
+    Some(right)
+}
+
+/// Calculate the quadratic form `x' A x`
+pub fn quadratic_form(a: &[Vec<f64>], x: &[f64]) -> f64 {
+    multiply_vector(a, x)
+        .iter()
+        .zip(x)
+        .map(|(ax, x)| ax * x)
+        .sum()
+}
+
+/// Factor a symmetric positive definite matrix as `L L'`, returning the lower triangular `L`
+///
+/// Returns `None` if the matrix is not positive definite.
+pub fn cholesky(a: &[Vec<f64>]) -> Option<Matrix> {
+    let n = a.len();
+    let mut lower = vec![vec![0.0; n]; n];
+
+    for i in 0..n {
+        for j in 0..=i {
+            let sum: f64 = (0..j).map(|k| lower[i][k] * lower[j][k]).sum();
+
+            if i == j {
+                let pivot = a[i][i] - sum;
+
+                if pivot.is_nan() || pivot <= 0.0 {
+                    return None;
+                }
+
+                lower[i][j] = pivot.sqrt();
+            } else {
+                lower[i][j] = (a[i][j] - sum) / lower[j][j];
+            }
+        }
+    }
+
+    Some(lower)
+}
+
+/// Eigen decomposition of a symmetric matrix by the cyclic Jacobi method
+///
+/// Returns the eigenvalues in descending order and a matrix whose columns are the corresponding
+/// unit eigenvectors.
+pub fn symmetric_eigen(a: &[Vec<f64>]) -> (Vec<f64>, Matrix) {
+    let n = a.len();
+    let mut d = a.to_vec();
+    let mut vectors = identity(n);
+
+    let scale: f64 = a.iter().flatten().map(|v| v * v).sum::<f64>().sqrt();
+
+    for _ in 0..100 {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |j| *j != i).map(move |j| (i, j)))
+            .map(|(i, j)| d[i][j] * d[i][j])
+            .sum::<f64>()
+            .sqrt();
+
+        if off_diagonal <= f64::EPSILON * scale {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if d[p][q] == 0.0 {
+                    continue;
+                }
+
+                // Rotate to zero the (p, q) element, choosing the smaller rotation angle.
+                let theta = (d[q][q] - d[p][p]) / (2.0 * d[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for row in d.iter_mut() {
+                    let (dkp, dkq) = (row[p], row[q]);
+                    row[p] = c * dkp - s * dkq;
+                    row[q] = s * dkp + c * dkq;
+                }
+
+                let (upper, lower) = d.split_at_mut(q);
+
+                for (dpk, dqk) in upper[p].iter_mut().zip(lower[0].iter_mut()) {
+                    let (x, y) = (*dpk, *dqk);
+                    *dpk = c * x - s * y;
+                    *dqk = s * x + c * y;
+                }
+
+                for row in vectors.iter_mut() {
+                    let (vp, vq) = (row[p], row[q]);
+                    row[p] = c * vp - s * vq;
+                    row[q] = s * vp + c * vq;
+                }
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|i, j| d[*j][*j].total_cmp(&d[*i][*i]));
+
+    let values = order.iter().map(|i| d[*i][*i]).collect();
+    let vectors = vectors
+        .iter()
+        .map(|row| order.iter().map(|i| row[*i]).collect())
+        .collect();
+
+    (values, vectors)
+}
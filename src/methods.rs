@@ -1,3 +1,5 @@
+use crate::optimize::bisection;
+
 /// Calculate the net present value of a series of cash flows, discounted by a rate
 pub fn net_present_value(initial_cost: f64, cash_flows: Vec<f64>, discount_rate: f64) -> f64 {
     let mut npv = 0.0;
@@ -24,3 +26,214 @@ pub fn internal_rate_of_return(initial_cost: f64, cash_flows: Vec<f64>) -> f64 {
 
     irr
 }
+
+/// Error type for time-value-of-money calculations
+#[derive(Debug)]
+pub enum Error {
+    /// The discount rate must exceed the growth rate for a perpetuity to have a finite value
+    NonConvergentPerpetuity,
+    /// No solution exists for the requested unknown given the other inputs
+    NoSolution,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Timing of the payments within each period of an annuity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PaymentTiming {
+    /// Payments are made at the end of each period (ordinary annuity)
+    #[default]
+    End,
+    /// Payments are made at the beginning of each period (annuity due)
+    Beginning,
+}
+
+impl PaymentTiming {
+    /// Factor applied to an ordinary annuity to account for payment timing
+    pub fn factor(&self, rate: f64) -> f64 {
+        match self {
+            PaymentTiming::End => 1.0,
+            PaymentTiming::Beginning => 1.0 + rate,
+        }
+    }
+}
+
+/// Calculate the discount factor for a periodic rate over a number of periods
+pub fn discount_factor(rate: f64, periods: f64) -> f64 {
+    1.0 / (1.0 + rate).powf(periods)
+}
+
+/// Calculate the present value of a fixed payment annuity
+pub fn present_value_annuity(payment: f64, rate: f64, periods: f64, timing: PaymentTiming) -> f64 {
+    if rate == 0.0 {
+        return payment * periods;
+    }
+
+    payment * (1.0 - discount_factor(rate, periods)) / rate * timing.factor(rate)
+}
+
+/// Calculate the future value of a fixed payment annuity
+pub fn future_value_annuity(payment: f64, rate: f64, periods: f64, timing: PaymentTiming) -> f64 {
+    if rate == 0.0 {
+        return payment * periods;
+    }
+
+    payment * ((1.0 + rate).powf(periods) - 1.0) / rate * timing.factor(rate)
+}
+
+/// Calculate the present value of a fixed payment perpetuity
+pub fn present_value_perpetuity(
+    payment: f64,
+    rate: f64,
+    timing: PaymentTiming,
+) -> Result<f64, Error> {
+    present_value_growing_perpetuity(payment, rate, 0.0, timing)
+}
+
+/// Calculate the present value of an annuity whose first payment grows at a constant rate each period
+pub fn present_value_growing_annuity(
+    payment: f64,
+    rate: f64,
+    growth_rate: f64,
+    periods: f64,
+    timing: PaymentTiming,
+) -> f64 {
+    // When the rates are equal each discounted payment is the same.
+    if rate == growth_rate {
+        return payment * periods / (1.0 + rate) * timing.factor(rate);
+    }
+
+    let ratio = ((1.0 + growth_rate) / (1.0 + rate)).powf(periods);
+
+    payment * (1.0 - ratio) / (rate - growth_rate) * timing.factor(rate)
+}
+
+/// Calculate the future value of an annuity whose first payment grows at a constant rate each period
+pub fn future_value_growing_annuity(
+    payment: f64,
+    rate: f64,
+    growth_rate: f64,
+    periods: f64,
+    timing: PaymentTiming,
+) -> f64 {
+    present_value_growing_annuity(payment, rate, growth_rate, periods, timing)
+        * (1.0 + rate).powf(periods)
+}
+
+/// Calculate the present value of a perpetuity whose first payment grows at a constant rate each period
+pub fn present_value_growing_perpetuity(
+    payment: f64,
+    rate: f64,
+    growth_rate: f64,
+    timing: PaymentTiming,
+) -> Result<f64, Error> {
+    if rate <= growth_rate {
+        return Err(Error::NonConvergentPerpetuity);
+    }
+
+    Ok(payment / (rate - growth_rate) * timing.factor(rate))
+}
+
+/// Time-value-of-money solver, following the sign convention of a financial calculator.
+///
+/// Cash received is positive and cash paid is negative, such that
+/// `PV(1 + r)^n + PMT * k * ((1 + r)^n - 1) / r + FV = 0`, where `k` is the payment timing factor.
+/// Any one of the five values can be solved for given the other four.
+#[derive(Debug, Clone, Default)]
+pub struct TimeValueOfMoney {
+    /// Number of periods
+    pub periods: f64,
+    /// Periodic interest rate
+    pub rate: f64,
+    /// Present value
+    pub present_value: f64,
+    /// Periodic payment
+    pub payment: f64,
+    /// Future value
+    pub future_value: f64,
+    /// Timing of the periodic payment
+    pub timing: PaymentTiming,
+}
+
+impl TimeValueOfMoney {
+    /// Residual of the time-value-of-money equation for the given values
+    fn residual(&self, periods: f64, rate: f64) -> f64 {
+        let growth = (1.0 + rate).powf(periods);
+
+        self.present_value * growth
+            + future_value_annuity(self.payment, rate, periods, self.timing)
+            + self.future_value
+    }
+
+    /// Solve for the present value given the other values
+    pub fn solve_present_value(&self) -> f64 {
+        -(future_value_annuity(self.payment, self.rate, self.periods, self.timing)
+            + self.future_value)
+            * discount_factor(self.rate, self.periods)
+    }
+
+    /// Solve for the future value given the other values
+    pub fn solve_future_value(&self) -> f64 {
+        -(self.present_value * (1.0 + self.rate).powf(self.periods)
+            + future_value_annuity(self.payment, self.rate, self.periods, self.timing))
+    }
+
+    /// Solve for the periodic payment given the other values
+    pub fn solve_payment(&self) -> Result<f64, Error> {
+        let annuity_factor = future_value_annuity(1.0, self.rate, self.periods, self.timing);
+
+        if annuity_factor == 0.0 {
+            return Err(Error::NoSolution);
+        }
+
+        Ok(
+            -(self.present_value * (1.0 + self.rate).powf(self.periods) + self.future_value)
+                / annuity_factor,
+        )
+    }
+
+    /// Solve for the number of periods given the other values
+    pub fn solve_periods(&self) -> Result<f64, Error> {
+        if self.rate == 0.0 {
+            if self.payment == 0.0 {
+                return Err(Error::NoSolution);
+            }
+
+            return Ok(-(self.present_value + self.future_value) / self.payment);
+        }
+
+        let annuity = self.payment * self.timing.factor(self.rate) / self.rate;
+        let growth = (annuity - self.future_value) / (annuity + self.present_value);
+
+        if !growth.is_finite() || growth <= 0.0 {
+            return Err(Error::NoSolution);
+        }
+
+        Ok(growth.ln() / (1.0 + self.rate).ln())
+    }
+
+    /// Solve for the periodic interest rate given the other values
+    pub fn solve_rate(&self) -> Result<f64, Error> {
+        let f = |rate: f64| self.residual(self.periods, rate);
+
+        // Search progressively wider brackets for a change of sign.
+        let mut upper = 0.0;
+
+        for step in [0.01, 0.1, 1.0, 10.0] {
+            let lower = upper;
+            upper = step;
+
+            if let Some(rate) = bisection(f, lower, upper) {
+                return Ok(rate);
+            }
+        }
+
+        bisection(f, -0.9999, 0.0).ok_or(Error::NoSolution)
+    }
+}
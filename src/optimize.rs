@@ -0,0 +1,143 @@
+/// Find the root of a function within a bracket using bisection
+///
+/// Returns `None` if the function does not change sign over the bracket.
+pub(crate) fn bisection<F: Fn(f64) -> f64>(f: F, mut lower: f64, mut upper: f64) -> Option<f64> {
+    let mut f_lower = f(lower);
+
+    if f_lower == 0.0 {
+        return Some(lower);
+    }
+
+    if f_lower.signum() == f(upper).signum() {
+        return None;
+    }
+
+    for _ in 0..200 {
+        let mid = (lower + upper) / 2.0;
+        let f_mid = f(mid);
+
+        if f_mid == 0.0 || (upper - lower) / 2.0 < 1e-12 {
+            return Some(mid);
+        }
+
+        if f_mid.signum() == f_lower.signum() {
+            lower = mid;
+            f_lower = f_mid;
+        } else {
+            upper = mid;
+        }
+    }
+
+    Some((lower + upper) / 2.0)
+}
+
+/// Minimize a function of several variables with the Nelder-Mead simplex method
+///
+/// The initial simplex perturbs each coordinate by 5% (or 0.00025 if it is zero). The search
+/// stops when the function values across the simplex differ by less than `tolerance`.
+pub(crate) fn nelder_mead<F: Fn(&[f64]) -> f64>(
+    f: F,
+    initial: &[f64],
+    tolerance: f64,
+    max_iterations: usize,
+) -> Vec<f64> {
+    let n = initial.len();
+
+    if n == 0 {
+        return vec![];
+    }
+
+    let mut simplex = vec![initial.to_vec()];
+
+    for i in 0..n {
+        let mut vertex = initial.to_vec();
+        vertex[i] = if vertex[i] == 0.0 {
+            0.00025
+        } else {
+            vertex[i] * 1.05
+        };
+        simplex.push(vertex);
+    }
+
+    // Treat NaN as infinitely bad so that invalid regions are moved away from.
+    let evaluate = |x: &[f64]| {
+        let value = f(x);
+
+        if value.is_nan() {
+            f64::INFINITY
+        } else {
+            value
+        }
+    };
+
+    let mut values: Vec<f64> = simplex.iter().map(|x| evaluate(x)).collect();
+
+    for _ in 0..max_iterations {
+        let mut order: Vec<usize> = (0..=n).collect();
+        order.sort_by(|a, b| values[*a].total_cmp(&values[*b]));
+        simplex = order.iter().map(|i| simplex[*i].clone()).collect();
+        values = order.iter().map(|i| values[*i]).collect();
+
+        if (values[n] - values[0]).abs() <= tolerance * (1.0 + values[0].abs()) {
+            break;
+        }
+
+        let centroid: Vec<f64> = (0..n)
+            .map(|j| simplex[..n].iter().map(|x| x[j]).sum::<f64>() / n as f64)
+            .collect();
+        let towards = |coefficient: f64| -> Vec<f64> {
+            centroid
+                .iter()
+                .zip(&simplex[n])
+                .map(|(c, w)| c + coefficient * (w - c))
+                .collect()
+        };
+
+        let reflected = towards(-1.0);
+        let f_reflected = evaluate(&reflected);
+
+        if f_reflected < values[0] {
+            let expanded = towards(-2.0);
+            let f_expanded = evaluate(&expanded);
+
+            if f_expanded < f_reflected {
+                simplex[n] = expanded;
+                values[n] = f_expanded;
+            } else {
+                simplex[n] = reflected;
+                values[n] = f_reflected;
+            }
+        } else if f_reflected < values[n - 1] {
+            simplex[n] = reflected;
+            values[n] = f_reflected;
+        } else {
+            let contracted = if f_reflected < values[n] {
+                towards(-0.5)
+            } else {
+                towards(0.5)
+            };
+            let f_contracted = evaluate(&contracted);
+
+            if f_contracted < values[n].min(f_reflected) {
+                simplex[n] = contracted;
+                values[n] = f_contracted;
+            } else {
+                // Shrink the simplex towards the best vertex.
+                for i in 1..=n {
+                    simplex[i] = simplex[0]
+                        .iter()
+                        .zip(&simplex[i])
+                        .map(|(b, x)| b + 0.5 * (x - b))
+                        .collect();
+                    values[i] = evaluate(&simplex[i]);
+                }
+            }
+        }
+    }
+
+    let best = (0..=n)
+        .min_by(|a, b| values[*a].total_cmp(&values[*b]))
+        .unwrap_or(0);
+
+    simplex.swap_remove(best)
+}
@@ -0,0 +1,175 @@
+use crate::drawdown::Drawdowns;
+use crate::stats::{RunningCovariance, Statistics, StatsError};
+
+/// Return a non-zero denominator or a degenerate variance error
+fn nonzero(denominator: f64) -> Result<f64, StatsError> {
+    if denominator == 0.0 {
+        return Err(StatsError::DegenerateVariance);
+    }
+
+    Ok(denominator)
+}
+
+/// Risk-adjusted performance ratios of periodic returns
+///
+/// Rates and targets are per period, matching `Statistics::sharpe_ratio`, and results are
+/// annualized over `periods_per_year` periods (1 for no annualization, or
+/// `TRADING_DAYS_PER_YEAR` for daily returns).
+pub trait PerformanceStatistics: Statistics {
+    /// Return the values of a benchmark, ensuring it is aligned with these returns
+    fn try_aligned<'a, S: Statistics + ?Sized>(
+        &self,
+        benchmark: &'a S,
+    ) -> Result<(&[f64], &'a [f64]), StatsError> {
+        let values = self.try_values(2)?;
+        let benchmark = benchmark.try_values(2)?;
+
+        if values.len() != benchmark.len() {
+            return Err(StatsError::LengthMismatch {
+                expected: values.len(),
+                actual: benchmark.len(),
+            });
+        }
+
+        Ok((values, benchmark))
+    }
+
+    /// Calculate the compound annual growth rate of the returns
+    fn annualized_return(&self, periods_per_year: f64) -> Result<f64, StatsError> {
+        Ok((1.0 + self.try_geometric_mean()?).powf(periods_per_year) - 1.0)
+    }
+
+    /// Calculate the annualized semideviation of returns below a target
+    ///
+    /// Shortfalls are averaged over all periods, following Sortino and Price (1994).
+    fn target_semideviation(&self, target: f64, periods_per_year: f64) -> Result<f64, StatsError> {
+        let values = self.try_values(1)?;
+
+        let shortfall = values
+            .iter()
+            .map(|v| (v - target).min(0.0).powi(2))
+            .sum::<f64>()
+            / values.len() as f64;
+
+        Ok((shortfall * periods_per_year).sqrt())
+    }
+
+    /// Calculate the Sortino Ratio, the annualized excess return over a target per unit of
+    /// target semideviation
+    fn sortino_ratio(&self, target: f64, periods_per_year: f64) -> Result<f64, StatsError> {
+        let excess = (self.try_population_mean()? - target) * periods_per_year;
+
+        Ok(excess / nonzero(self.target_semideviation(target, periods_per_year)?)?)
+    }
+
+    /// Calculate the Treynor Ratio, the annualized excess return per unit of beta to a benchmark
+    fn treynor_ratio<S: Statistics + ?Sized>(
+        &self,
+        benchmark: &S,
+        risk_free_rate: f64,
+        periods_per_year: f64,
+    ) -> Result<f64, StatsError> {
+        let (values, benchmark) = self.try_aligned(benchmark)?;
+        let covariance = RunningCovariance::from_values(values, benchmark);
+        let beta = covariance.sample_covariance() / nonzero(covariance.y().sample_variance())?;
+
+        Ok((covariance.x().mean() - risk_free_rate) * periods_per_year / nonzero(beta)?)
+    }
+
+    /// Calculate the Information Ratio, the annualized active return over a benchmark per unit
+    /// of tracking error
+    fn information_ratio<S: Statistics + ?Sized>(
+        &self,
+        benchmark: &S,
+        periods_per_year: f64,
+    ) -> Result<f64, StatsError> {
+        let (values, benchmark) = self.try_aligned(benchmark)?;
+        let active: Vec<f64> = values.iter().zip(benchmark).map(|(r, b)| r - b).collect();
+
+        Ok(active.try_sharpe_ratio(0.0)? * periods_per_year.sqrt())
+    }
+
+    /// Calculate the Calmar Ratio, the annualized excess return per unit of maximum drawdown
+    fn calmar_ratio(&self, risk_free_rate: f64, periods_per_year: f64) -> Result<f64, StatsError> {
+        let max_drawdown = Drawdowns::from_returns(self)?
+            .max_drawdown()
+            .map_or(0.0, |episode| episode.depth);
+
+        Ok(
+            (self.annualized_return(periods_per_year)? - risk_free_rate * periods_per_year)
+                / nonzero(max_drawdown)?,
+        )
+    }
+
+    /// Calculate the Omega Ratio, the ratio of gains above a threshold to losses below it
+    ///
+    /// The ratio is the same at any frequency, so it takes no annualization.
+    fn omega_ratio(&self, threshold: f64) -> Result<f64, StatsError> {
+        let values = self.try_values(1)?;
+        let gains: f64 = values.iter().map(|v| (v - threshold).max(0.0)).sum();
+        let losses: f64 = values.iter().map(|v| (threshold - v).max(0.0)).sum();
+
+        Ok(gains / nonzero(losses)?)
+    }
+
+    /// Calculate the Sterling Ratio, the annualized excess return per unit of the average depth
+    /// of the `largest` deepest drawdowns (Bacon, 2008)
+    fn sterling_ratio(
+        &self,
+        risk_free_rate: f64,
+        periods_per_year: f64,
+        largest: usize,
+    ) -> Result<f64, StatsError> {
+        let drawdowns = Drawdowns::from_returns(self)?;
+        let deepest = drawdowns.top(largest);
+        let average = deepest.iter().map(|e| e.depth).sum::<f64>() / deepest.len().max(1) as f64;
+
+        Ok(
+            (self.annualized_return(periods_per_year)? - risk_free_rate * periods_per_year)
+                / nonzero(average)?,
+        )
+    }
+
+    /// Calculate the Burke Ratio, the annualized excess return per unit of the square root of
+    /// the sum of squared drawdown depths
+    fn burke_ratio(&self, risk_free_rate: f64, periods_per_year: f64) -> Result<f64, StatsError> {
+        let drawdowns = Drawdowns::from_returns(self)?;
+        let risk = drawdowns
+            .episodes
+            .iter()
+            .map(|e| e.depth * e.depth)
+            .sum::<f64>()
+            .sqrt();
+
+        Ok(
+            (self.annualized_return(periods_per_year)? - risk_free_rate * periods_per_year)
+                / nonzero(risk)?,
+        )
+    }
+
+    /// Calculate the Ulcer Index, the root mean square of the drawdowns from the running peak
+    fn ulcer_index(&self) -> Result<f64, StatsError> {
+        let drawdowns = Drawdowns::from_returns(self)?.drawdowns;
+
+        Ok((drawdowns.iter().map(|d| d * d).sum::<f64>() / drawdowns.len() as f64).sqrt())
+    }
+
+    /// Calculate the M² (Modigliani) measure, the annualized return of the portfolio leveraged
+    /// to the volatility of a benchmark
+    fn m_squared<S: Statistics + ?Sized>(
+        &self,
+        benchmark: &S,
+        risk_free_rate: f64,
+        periods_per_year: f64,
+    ) -> Result<f64, StatsError> {
+        let (_, benchmark_values) = self.try_aligned(benchmark)?;
+        let sharpe_ratio = self.try_sharpe_ratio(risk_free_rate)?;
+
+        Ok(
+            (risk_free_rate + sharpe_ratio * benchmark_values.try_sample_std_dev()?)
+                * periods_per_year,
+        )
+    }
+}
+
+impl<T: Statistics + ?Sized> PerformanceStatistics for T {}
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
+use std::convert::Infallible;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::inflation::{self, CpiSeries};
 use crate::stock_data::{Returns, StockData};
 
 pub type Ticker = String;
@@ -52,12 +54,112 @@ impl Portfolio {
         }
 
         for (_, asset) in self.assets.iter_mut() {
-            asset.stock_data.data.sort_by(|a, b| a.date.cmp(&b.date));
+            asset.stock_data.data.sort_by_key(|d| d.date);
         }
 
         Ok(())
     }
 
+    // Nominal holding period returns of each asset
+    pub fn asset_returns(&self) -> HashMap<Ticker, Returns> {
+        self.assets
+            .iter()
+            .map(|(ticker, asset)| (ticker.clone(), asset.stock_data.returns()))
+            .collect()
+    }
+
+    // Tickers of the assets in alphabetical order
+    pub fn tickers(&self) -> Vec<Ticker> {
+        let mut tickers: Vec<Ticker> = self.assets.keys().cloned().collect();
+        tickers.sort();
+
+        tickers
+    }
+
+    // Weight of each asset by amount invested, in the order of `tickers`
+    pub fn weights(&self) -> Vec<f64> {
+        let total: f64 = self.assets.values().map(|a| a.amount_invested).sum();
+
+        self.tickers()
+            .iter()
+            .map(|ticker| self.assets[ticker].amount_invested / total)
+            .collect()
+    }
+
+    // Dated returns of each asset from a function of its stock data, in the order of `tickers`
+    fn dated_returns<E>(
+        &self,
+        returns: impl Fn(&StockData) -> Result<Returns, E>,
+    ) -> Result<Vec<HashMap<DateTime<Utc>, f64>>, E> {
+        // Position of each date any asset trades on
+        let mut dates: Vec<DateTime<Utc>> = self
+            .assets
+            .values()
+            .flat_map(|asset| asset.stock_data.data.iter().map(|bar| bar.date))
+            .collect();
+        dates.sort();
+        dates.dedup();
+        let position: HashMap<DateTime<Utc>, usize> =
+            dates.into_iter().enumerate().map(|(i, d)| (d, i)).collect();
+
+        self.tickers()
+            .iter()
+            .map(|ticker| {
+                let stock_data = &self.assets[ticker].stock_data;
+
+                // The first bar has no prior close, so it has no return, and a return across
+                // missing bars spans several periods, so it is dropped.
+                Ok(stock_data
+                    .data
+                    .windows(2)
+                    .zip(returns(stock_data)?.0.into_iter().skip(1))
+                    .filter(|(bars, _)| position[&bars[0].date] + 1 == position[&bars[1].date])
+                    .map(|(bars, r)| (bars[1].date, r))
+                    .collect())
+            })
+            .collect()
+    }
+
+    // Dated one-period returns of each asset, in the order of `tickers`, leaving out the return
+    // after a date on which the asset has no bar but another asset does
+    pub fn dated_asset_returns(&self) -> Vec<HashMap<DateTime<Utc>, f64>> {
+        let Ok(returns) =
+            self.dated_returns(|stock_data| Ok::<_, Infallible>(stock_data.returns()));
+
+        returns
+    }
+
+    // One-period returns of every asset on the dates all assets have a return, in the order of
+    // `tickers`
+    pub fn aligned_returns(&self) -> Vec<(DateTime<Utc>, Vec<f64>)> {
+        align(self.dated_asset_returns())
+    }
+
+    // Value-weighted returns of the portfolio on the dates all assets have a return, which are
+    // NaN if nothing is invested
+    pub fn returns(&self) -> Returns {
+        weighted_returns(&self.aligned_returns(), &self.weights())
+    }
+
+    // Real (inflation-adjusted) value-weighted returns of the portfolio on the dates all assets
+    // have a return
+    pub fn real_returns(&self, cpi: &CpiSeries) -> Result<Returns, inflation::Error> {
+        let real = align(self.dated_returns(|stock_data| cpi.real_returns(stock_data))?);
+
+        Ok(weighted_returns(&real, &self.weights()))
+    }
+
+    // Real (inflation-adjusted) holding period returns of each asset
+    pub fn real_asset_returns(
+        &self,
+        cpi: &CpiSeries,
+    ) -> Result<HashMap<Ticker, Returns>, inflation::Error> {
+        self.assets
+            .iter()
+            .map(|(ticker, asset)| Ok((ticker.clone(), cpi.real_returns(&asset.stock_data)?)))
+            .collect()
+    }
+
     // Save portfolio as JSON file
     pub fn save(&self, file: std::path::PathBuf) -> Result<(), Box<dyn std::error::Error>> {
         let file = std::fs::File::create(file)?;
@@ -72,3 +174,32 @@ impl Portfolio {
         serde_json::from_reader(reader).map_err(|e| e.into())
     }
 }
+
+// Match up dated returns of each asset on the dates all assets have a return
+fn align(returns: Vec<HashMap<DateTime<Utc>, f64>>) -> Vec<(DateTime<Utc>, Vec<f64>)> {
+    let Some((first, rest)) = returns.split_first() else {
+        return vec![];
+    };
+
+    let mut dates: Vec<DateTime<Utc>> = first
+        .keys()
+        .filter(|date| rest.iter().all(|r| r.contains_key(date)))
+        .copied()
+        .collect();
+    dates.sort();
+
+    dates
+        .into_iter()
+        .map(|date| (date, returns.iter().map(|r| r[&date]).collect()))
+        .collect()
+}
+
+// Weighted sum of the aligned returns of each asset on each date
+fn weighted_returns(aligned: &[(DateTime<Utc>, Vec<f64>)], weights: &[f64]) -> Returns {
+    Returns(
+        aligned
+            .iter()
+            .map(|(_, r)| r.iter().zip(weights).map(|(r, w)| r * w).sum())
+            .collect(),
+    )
+}
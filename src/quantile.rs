@@ -0,0 +1,174 @@
+use crate::stats::{Statistics, StatsError};
+
+/// The nine sample quantile definitions of Hyndman and Fan (1996)
+///
+/// Types 1-3 are discontinuous and types 4-9 interpolate linearly between order statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuantileMethod {
+    /// Inverse of the empirical distribution function (SAS-3, NumPy `inverted_cdf`)
+    Type1,
+    /// Inverse of the empirical distribution function, averaging at discontinuities (SAS-5)
+    Type2,
+    /// Nearest even order statistic (SAS-2, NumPy `closest_observation`)
+    Type3,
+    /// Linear interpolation of the empirical distribution function (SAS-1)
+    Type4,
+    /// Piecewise linear with knots at the midpoints of the steps (Hazen)
+    Type5,
+    /// Expected value of the order statistic, `(n + 1)p` (Minitab, SPSS, Excel `PERCENTILE.EXC`)
+    Type6,
+    /// Mode of the order statistic, `(n - 1)p + 1` (R and NumPy default, Excel `PERCENTILE.INC`)
+    #[default]
+    Type7,
+    /// Approximately median-unbiased, recommended by Hyndman and Fan
+    Type8,
+    /// Approximately unbiased for normally distributed data (Blom)
+    Type9,
+}
+
+/// Tolerance used to absorb floating-point error when locating an order statistic
+const FUZZ: f64 = 4.0 * f64::EPSILON;
+
+/// Calculate the quantile `probability` (between 0 and 1) of values sorted in ascending order
+pub fn quantile_sorted(
+    sorted: &[f64],
+    probability: f64,
+    method: QuantileMethod,
+) -> Result<f64, StatsError> {
+    if sorted.is_empty() {
+        return Err(StatsError::EmptyInput);
+    }
+
+    if !(0.0..=1.0).contains(&probability) {
+        return Err(StatsError::InvalidPercentile(probability * 100.0));
+    }
+
+    let n = sorted.len() as f64;
+    let p = probability;
+
+    let m = match method {
+        QuantileMethod::Type1 | QuantileMethod::Type2 | QuantileMethod::Type4 => 0.0,
+        QuantileMethod::Type3 => -0.5,
+        QuantileMethod::Type5 => 0.5,
+        QuantileMethod::Type6 => p,
+        QuantileMethod::Type7 => 1.0 - p,
+        QuantileMethod::Type8 => (p + 1.0) / 3.0,
+        QuantileMethod::Type9 => p / 4.0 + 3.0 / 8.0,
+    };
+
+    // One-based index j of the lower order statistic and the fraction g above it
+    let h = n * p + m;
+    let j = (h + FUZZ).floor();
+    let g = if (h - j).abs() < FUZZ { 0.0 } else { h - j };
+
+    let gamma = match method {
+        QuantileMethod::Type1 => {
+            if g > 0.0 {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        QuantileMethod::Type2 => {
+            if g > 0.0 {
+                1.0
+            } else {
+                0.5
+            }
+        }
+        QuantileMethod::Type3 => {
+            if g == 0.0 && j % 2.0 == 0.0 {
+                0.0
+            } else {
+                1.0
+            }
+        }
+        _ => g,
+    };
+
+    // Order statistics beyond either end are clamped to the first and last values.
+    let order_statistic = |k: f64| sorted[(k.clamp(1.0, n) as usize) - 1];
+
+    let lower = order_statistic(j);
+    let upper = order_statistic(j + 1.0);
+
+    if gamma == 0.0 {
+        Ok(lower)
+    } else {
+        Ok((1.0 - gamma) * lower + gamma * upper)
+    }
+}
+
+/// Minimum, lower quartile, median, upper quartile and maximum of a distribution
+#[derive(Debug, Clone, PartialEq)]
+pub struct FiveNumberSummary {
+    pub min: f64,
+    pub lower_quartile: f64,
+    pub median: f64,
+    pub upper_quartile: f64,
+    pub max: f64,
+}
+
+/// Quantile estimates of a distribution using a choice of sample quantile definitions
+pub trait QuantileStatistics: Statistics {
+    /// Calculate the quantile at each probability (between 0 and 1)
+    fn try_quantiles(
+        &self,
+        probabilities: &[f64],
+        method: QuantileMethod,
+    ) -> Result<Vec<f64>, StatsError> {
+        let mut sorted = self.try_values(1)?.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        probabilities
+            .iter()
+            .map(|p| quantile_sorted(&sorted, *p, method))
+            .collect()
+    }
+
+    /// Calculate the quantile at `probability` (between 0 and 1)
+    fn try_quantile(&self, probability: f64, method: QuantileMethod) -> Result<f64, StatsError> {
+        Ok(self.try_quantiles(&[probability], method)?[0])
+    }
+
+    /// Calculate the quantile at `probability` (between 0 and 1)
+    fn quantile(&self, probability: f64, method: QuantileMethod) -> f64 {
+        self.try_quantile(probability, method).unwrap_or(f64::NAN)
+    }
+
+    /// Calculate the lower quartile, median and upper quartile
+    fn quartiles(&self, method: QuantileMethod) -> Result<[f64; 3], StatsError> {
+        let q = self.try_quantiles(&[0.25, 0.5, 0.75], method)?;
+
+        Ok([q[0], q[1], q[2]])
+    }
+
+    /// Calculate the interquartile range
+    fn interquartile_range(&self, method: QuantileMethod) -> Result<f64, StatsError> {
+        let [lower, _, upper] = self.quartiles(method)?;
+
+        Ok(upper - lower)
+    }
+
+    /// Calculate the nine deciles, from the 10th to the 90th percentile
+    fn deciles(&self, method: QuantileMethod) -> Result<Vec<f64>, StatsError> {
+        let probabilities = (1..10).map(|d| d as f64 / 10.0).collect::<Vec<f64>>();
+
+        self.try_quantiles(&probabilities, method)
+    }
+
+    /// Calculate the minimum, quartiles and maximum
+    fn five_number_summary(&self, method: QuantileMethod) -> Result<FiveNumberSummary, StatsError> {
+        let q = self.try_quantiles(&[0.0, 0.25, 0.5, 0.75, 1.0], method)?;
+
+        Ok(FiveNumberSummary {
+            min: q[0],
+            lower_quartile: q[1],
+            median: q[2],
+            upper_quartile: q[3],
+            max: q[4],
+        })
+    }
+}
+
+impl<T: Statistics + ?Sized> QuantileStatistics for T {}
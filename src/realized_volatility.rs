@@ -0,0 +1,193 @@
+use std::f64::consts::LN_2;
+
+use crate::rolling::Window;
+use crate::stock_data::{Data, StockData};
+
+/// Error type for realized volatility estimators
+#[derive(Debug)]
+pub enum Error {
+    /// There are too few bars for the estimator
+    InsufficientData { required: usize, actual: usize },
+    /// A bar has a non-positive price, or a high or low that does not contain its open and close
+    InvalidBar(usize),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Estimator of volatility from daily open, high, low and close prices
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolatilityEstimator {
+    /// Sample standard deviation of close-to-close log returns
+    CloseToClose,
+    /// High-low range estimator of Parkinson (1980), assuming no drift or opening jumps
+    Parkinson,
+    /// Open-high-low-close estimator of Garman and Klass (1980), assuming no drift or opening jumps
+    GarmanKlass,
+    /// Drift-independent estimator of Rogers and Satchell (1991), assuming no opening jumps
+    RogersSatchell,
+    /// Drift-independent estimator of Yang and Zhang (2000) that accounts for opening jumps
+    YangZhang,
+}
+
+impl VolatilityEstimator {
+    /// Whether the estimator uses the previous bar's close, so the first bar is not used
+    fn uses_previous_close(&self) -> bool {
+        matches!(
+            self,
+            VolatilityEstimator::CloseToClose | VolatilityEstimator::YangZhang
+        )
+    }
+
+    /// Minimum number of usable bars for a variance estimate
+    fn min_bars(&self) -> usize {
+        match self {
+            VolatilityEstimator::CloseToClose | VolatilityEstimator::YangZhang => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// Log price relatives of a bar
+#[derive(Debug, Clone, Copy)]
+struct BarTerms {
+    /// Overnight return, `ln(open / previous close)`
+    overnight: f64,
+    /// Close-to-close return, `ln(close / previous close)`
+    close_to_close: f64,
+    /// `ln(high / open)`
+    high: f64,
+    /// `ln(low / open)`
+    low: f64,
+    /// Open-to-close return, `ln(close / open)`
+    close: f64,
+}
+
+/// Calculate the log price relatives of each bar, which are NaN for the first bar's previous close
+fn bar_terms(data: &[Data]) -> Result<Vec<BarTerms>, Error> {
+    data.iter()
+        .enumerate()
+        .map(|(i, bar)| {
+            if [bar.open, bar.high, bar.low, bar.close]
+                .iter()
+                .any(|p| p.is_nan() || *p <= 0.0)
+                || bar.high < bar.open.max(bar.close)
+                || bar.low > bar.open.min(bar.close)
+            {
+                return Err(Error::InvalidBar(i));
+            }
+
+            let previous_close = if i == 0 { f64::NAN } else { data[i - 1].close };
+
+            Ok(BarTerms {
+                overnight: (bar.open / previous_close).ln(),
+                close_to_close: (bar.close / previous_close).ln(),
+                high: (bar.high / bar.open).ln(),
+                low: (bar.low / bar.open).ln(),
+                close: (bar.close / bar.open).ln(),
+            })
+        })
+        .collect()
+}
+
+/// Sample variance of a set of values
+fn sample_variance(values: impl Iterator<Item = f64> + Clone) -> f64 {
+    let n = values.clone().count() as f64;
+    let mean = values.clone().sum::<f64>() / n;
+
+    values.map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)
+}
+
+/// Estimate the per-period variance from the terms of consecutive bars
+fn variance(terms: &[BarTerms], estimator: VolatilityEstimator) -> f64 {
+    let n = terms.len() as f64;
+    let rogers_satchell = || {
+        terms
+            .iter()
+            .map(|t| t.high * (t.high - t.close) + t.low * (t.low - t.close))
+            .sum::<f64>()
+            / n
+    };
+
+    match estimator {
+        VolatilityEstimator::CloseToClose => {
+            sample_variance(terms.iter().map(|t| t.close_to_close))
+        }
+        VolatilityEstimator::Parkinson => {
+            terms.iter().map(|t| (t.high - t.low).powi(2)).sum::<f64>() / (4.0 * LN_2 * n)
+        }
+        VolatilityEstimator::GarmanKlass => {
+            terms
+                .iter()
+                .map(|t| 0.5 * (t.high - t.low).powi(2) - (2.0 * LN_2 - 1.0) * t.close.powi(2))
+                .sum::<f64>()
+                / n
+        }
+        VolatilityEstimator::RogersSatchell => rogers_satchell(),
+        VolatilityEstimator::YangZhang => {
+            // Weight chosen by Yang and Zhang to minimize the variance of the estimator.
+            let k = 0.34 / (1.34 + (n + 1.0) / (n - 1.0));
+
+            sample_variance(terms.iter().map(|t| t.overnight))
+                + k * sample_variance(terms.iter().map(|t| t.close))
+                + (1.0 - k) * rogers_satchell()
+        }
+    }
+}
+
+/// Estimate the volatility of a stock from its bars, annualized by the number of periods per year
+///
+/// Bars must be in chronological order, as for `StockData::returns`.
+pub fn volatility(
+    stock_data: &StockData,
+    estimator: VolatilityEstimator,
+    periods_per_year: f64,
+) -> Result<f64, Error> {
+    let terms = bar_terms(&stock_data.data)?;
+    let usable = if estimator.uses_previous_close() {
+        terms.get(1..).unwrap_or_default()
+    } else {
+        &terms[..]
+    };
+
+    if usable.len() < estimator.min_bars() {
+        return Err(Error::InsufficientData {
+            required: estimator.min_bars() + usize::from(estimator.uses_previous_close()),
+            actual: stock_data.data.len(),
+        });
+    }
+
+    Ok((variance(usable, estimator) * periods_per_year).sqrt())
+}
+
+/// Estimate the volatility over a rolling window of bars, annualized by the number of periods
+/// per year
+///
+/// The output is aligned with the bars, with NaN until a window has `min_periods` usable bars.
+pub fn rolling_volatility(
+    stock_data: &StockData,
+    estimator: VolatilityEstimator,
+    window: Window,
+    periods_per_year: f64,
+) -> Result<Vec<f64>, Error> {
+    let terms = bar_terms(&stock_data.data)?;
+    let first = usize::from(estimator.uses_previous_close());
+    let min_periods = window.min_periods.max(estimator.min_bars());
+
+    Ok((0..terms.len())
+        .map(|i| {
+            let start = (i + 1).saturating_sub(window.size).max(first);
+
+            if i + 1 < start + min_periods {
+                return f64::NAN;
+            }
+
+            (variance(&terms[start..=i], estimator) * periods_per_year).sqrt()
+        })
+        .collect())
+}
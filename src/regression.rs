@@ -0,0 +1,352 @@
+use crate::matrix::{self, Matrix};
+use crate::special;
+use crate::stats::{Statistics, StatsError};
+
+/// Error type for linear regression
+#[derive(Debug)]
+pub enum Error {
+    /// The response or a regressor is empty, too short, or contains NaN
+    Stats(StatsError),
+    /// The design matrix has no regressors
+    EmptyDesign,
+    /// The design matrix, weights and response have inconsistent dimensions
+    DimensionMismatch { expected: usize, actual: usize },
+    /// There are no more observations than coefficients
+    InsufficientObservations { required: usize, actual: usize },
+    /// Weights must be positive and finite
+    InvalidWeights,
+    /// The regressors are perfectly collinear
+    SingularMatrix,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<StatsError> for Error {
+    fn from(e: StatsError) -> Self {
+        Error::Stats(e)
+    }
+}
+
+/// Estimator of the covariance matrix of the coefficients
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CovarianceEstimator {
+    /// Homoskedastic, serially uncorrelated errors
+    #[default]
+    Classical,
+    /// Newey-West heteroskedasticity and autocorrelation consistent (HAC) estimator with a
+    /// Bartlett kernel. Without a lag, `floor(4 * (n / 100)^(2/9))` lags are used.
+    NeweyWest { lags: Option<usize> },
+}
+
+/// Configuration of an ordinary or weighted least squares regression
+#[derive(Debug, Clone)]
+pub struct LinearRegression {
+    /// Whether to add a constant term as the first coefficient
+    pub intercept: bool,
+    /// Observation weights for weighted least squares
+    pub weights: Option<Vec<f64>>,
+    pub covariance: CovarianceEstimator,
+}
+
+impl Default for LinearRegression {
+    fn default() -> Self {
+        Self {
+            intercept: true,
+            weights: None,
+            covariance: CovarianceEstimator::Classical,
+        }
+    }
+}
+
+/// A fitted linear regression
+#[derive(Debug, Clone)]
+pub struct RegressionResult {
+    /// Estimated coefficients, starting with the intercept if one was fitted
+    pub coefficients: Vec<f64>,
+    pub standard_errors: Vec<f64>,
+    pub t_statistics: Vec<f64>,
+    pub p_values: Vec<f64>,
+    /// Covariance matrix of the coefficients
+    pub covariance: Matrix,
+    pub r_squared: f64,
+    pub adjusted_r_squared: f64,
+    /// Residuals `y - X b` of each observation
+    pub residuals: Vec<f64>,
+    pub fitted_values: Vec<f64>,
+    /// Residual degrees of freedom, `n - k`
+    pub degrees_of_freedom: usize,
+    pub intercept: bool,
+}
+
+impl RegressionResult {
+    /// Predict the response for a row of regressors, excluding the intercept column
+    pub fn predict(&self, regressors: &[f64]) -> f64 {
+        let (constant, slopes) = if self.intercept {
+            (self.coefficients[0], &self.coefficients[1..])
+        } else {
+            (0.0, &self.coefficients[..])
+        };
+
+        constant
+            + slopes
+                .iter()
+                .zip(regressors)
+                .map(|(b, x)| b * x)
+                .sum::<f64>()
+    }
+}
+
+impl LinearRegression {
+    /// Create an ordinary least squares regression with an intercept
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fit the regression through the origin
+    pub fn without_intercept(mut self) -> Self {
+        self.intercept = false;
+        self
+    }
+
+    /// Fit by weighted least squares, minimizing `sum(w * e^2)`
+    pub fn with_weights(mut self, weights: Vec<f64>) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+
+    /// Estimate standard errors with the Newey-West HAC estimator
+    pub fn with_newey_west(mut self, lags: Option<usize>) -> Self {
+        self.covariance = CovarianceEstimator::NeweyWest { lags };
+        self
+    }
+
+    /// Regress a response series on aligned regressor series, such as `Returns`
+    pub fn fit_series<S, R>(
+        &self,
+        response: &S,
+        regressors: &[&R],
+    ) -> Result<RegressionResult, Error>
+    where
+        S: Statistics + ?Sized,
+        R: Statistics + ?Sized,
+    {
+        let n = response.count();
+        let mut design = vec![Vec::with_capacity(regressors.len()); n];
+
+        for regressor in regressors {
+            let values = regressor.try_values(1)?;
+
+            if values.len() != n {
+                return Err(Error::DimensionMismatch {
+                    expected: n,
+                    actual: values.len(),
+                });
+            }
+
+            for (row, value) in design.iter_mut().zip(values) {
+                row.push(*value);
+            }
+        }
+
+        self.fit(response, &design)
+    }
+
+    /// Regress a response on a design matrix with one row of regressors per observation
+    ///
+    /// The intercept column is added automatically and should not be included in the design.
+    pub fn fit<S: Statistics + ?Sized>(
+        &self,
+        response: &S,
+        design: &[Vec<f64>],
+    ) -> Result<RegressionResult, Error> {
+        let y = response.try_values(1)?;
+        let n = y.len();
+
+        if design.len() != n {
+            return Err(Error::DimensionMismatch {
+                expected: n,
+                actual: design.len(),
+            });
+        }
+
+        let regressors = design[0].len();
+
+        if let Some(row) = design.iter().find(|row| row.len() != regressors) {
+            return Err(Error::DimensionMismatch {
+                expected: regressors,
+                actual: row.len(),
+            });
+        }
+
+        if design.iter().flatten().any(|v| v.is_nan()) {
+            return Err(StatsError::NotANumber.into());
+        }
+
+        let x: Matrix = design
+            .iter()
+            .map(|row| {
+                let constant = self.intercept.then_some(1.0);
+                constant.into_iter().chain(row.iter().copied()).collect()
+            })
+            .collect();
+        let k = x[0].len();
+
+        if k == 0 {
+            return Err(Error::EmptyDesign);
+        }
+
+        if n <= k {
+            return Err(Error::InsufficientObservations {
+                required: k + 1,
+                actual: n,
+            });
+        }
+
+        let weights = match &self.weights {
+            Some(weights) => {
+                if weights.len() != n {
+                    return Err(Error::DimensionMismatch {
+                        expected: n,
+                        actual: weights.len(),
+                    });
+                }
+
+                if weights.iter().any(|w| !(w.is_finite() && *w > 0.0)) {
+                    return Err(Error::InvalidWeights);
+                }
+
+                weights.clone()
+            }
+            None => vec![1.0; n],
+        };
+
+        // Weighted least squares is ordinary least squares on rows scaled by sqrt(w).
+        let root_weights: Vec<f64> = weights.iter().map(|w| w.sqrt()).collect();
+        let x_weighted: Matrix = x
+            .iter()
+            .zip(&root_weights)
+            .map(|(row, r)| row.iter().map(|v| v * r).collect())
+            .collect();
+        let y_weighted: Vec<f64> = y.iter().zip(&root_weights).map(|(v, r)| v * r).collect();
+
+        let x_transpose = matrix::transpose(&x_weighted);
+        let gram_inverse = matrix::inverse(&matrix::multiply(&x_transpose, &x_weighted))
+            .ok_or(Error::SingularMatrix)?;
+        let coefficients = matrix::multiply_vector(
+            &gram_inverse,
+            &matrix::multiply_vector(&x_transpose, &y_weighted),
+        );
+
+        let fitted_values = matrix::multiply_vector(&x, &coefficients);
+        let residuals: Vec<f64> = y.iter().zip(&fitted_values).map(|(y, f)| y - f).collect();
+        let weighted_residuals: Vec<f64> = residuals
+            .iter()
+            .zip(&root_weights)
+            .map(|(e, r)| e * r)
+            .collect();
+
+        let degrees_of_freedom = n - k;
+        let residual_sum_squares: f64 = weighted_residuals.iter().map(|e| e * e).sum();
+
+        // Without an intercept the total sum of squares is uncentered, as in R's `lm`.
+        let centre = if self.intercept {
+            weights.iter().zip(y).map(|(w, y)| w * y).sum::<f64>() / weights.iter().sum::<f64>()
+        } else {
+            0.0
+        };
+        let total_sum_squares: f64 = weights
+            .iter()
+            .zip(y)
+            .map(|(w, y)| w * (y - centre).powi(2))
+            .sum();
+
+        let r_squared = 1.0 - residual_sum_squares / total_sum_squares;
+        let model_degrees_of_freedom = n - usize::from(self.intercept);
+        let adjusted_r_squared =
+            1.0 - (1.0 - r_squared) * model_degrees_of_freedom as f64 / degrees_of_freedom as f64;
+
+        let covariance = match self.covariance {
+            CovarianceEstimator::Classical => {
+                let sigma_squared = residual_sum_squares / degrees_of_freedom as f64;
+
+                gram_inverse
+                    .iter()
+                    .map(|row| row.iter().map(|v| v * sigma_squared).collect())
+                    .collect()
+            }
+            CovarianceEstimator::NeweyWest { lags } => {
+                let lags = lags.unwrap_or((4.0 * (n as f64 / 100.0).powf(2.0 / 9.0)) as usize);
+                let meat = newey_west_meat(&x_weighted, &weighted_residuals, lags);
+
+                matrix::multiply(&matrix::multiply(&gram_inverse, &meat), &gram_inverse)
+            }
+        };
+
+        let standard_errors: Vec<f64> = (0..k).map(|i| covariance[i][i].sqrt()).collect();
+        let t_statistics: Vec<f64> = coefficients
+            .iter()
+            .zip(&standard_errors)
+            .map(|(b, se)| b / se)
+            .collect();
+
+        // Two-sided p-values from Student's t distribution with the residual degrees of freedom.
+        let nu = degrees_of_freedom as f64;
+        let p_values = t_statistics
+            .iter()
+            .map(|t| special::beta_inc(nu / 2.0, 0.5, nu / (nu + t * t)))
+            .collect();
+
+        Ok(RegressionResult {
+            coefficients,
+            standard_errors,
+            t_statistics,
+            p_values,
+            covariance,
+            r_squared,
+            adjusted_r_squared,
+            residuals,
+            fitted_values,
+            degrees_of_freedom,
+            intercept: self.intercept,
+        })
+    }
+}
+
+/// Newey-West estimate of the long-run covariance of the scores `x_t e_t`, using Bartlett weights
+fn newey_west_meat(x: &[Vec<f64>], residuals: &[f64], lags: usize) -> Matrix {
+    let k = x[0].len();
+    let scores: Matrix = x
+        .iter()
+        .zip(residuals)
+        .map(|(row, e)| row.iter().map(|v| v * e).collect())
+        .collect();
+
+    let mut meat = vec![vec![0.0; k]; k];
+
+    for lag in 0..=lags.min(scores.len() - 1) {
+        let weight = 1.0 - lag as f64 / (lags + 1) as f64;
+
+        for t in lag..scores.len() {
+            for i in 0..k {
+                for j in 0..k {
+                    let product = scores[t][i] * scores[t - lag][j];
+
+                    if lag == 0 {
+                        meat[i][j] += product;
+                    } else {
+                        // Add both the lagged term and its transpose.
+                        meat[i][j] += weight * (product + scores[t - lag][i] * scores[t][j]);
+                    }
+                }
+            }
+        }
+    }
+
+    meat
+}
@@ -0,0 +1,239 @@
+use crate::stats::Statistics;
+
+/// Decay factor used by RiskMetrics for daily EWMA volatility
+pub const RISKMETRICS_LAMBDA: f64 = 0.94;
+
+/// Size of a rolling window and the minimum number of values required for an output
+#[derive(Debug, Clone, Copy)]
+pub struct Window {
+    /// Number of values in a full window
+    pub size: usize,
+    /// Minimum number of values required before an output is produced
+    pub min_periods: usize,
+}
+
+impl Window {
+    /// Create a window that requires a full set of values
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            min_periods: size,
+        }
+    }
+
+    /// Set the minimum number of values required before an output is produced
+    pub fn with_min_periods(mut self, min_periods: usize) -> Self {
+        self.min_periods = min_periods;
+        self
+    }
+}
+
+/// Moments of the values currently inside a rolling window, updated as values enter and leave
+#[derive(Debug, Clone, Default)]
+struct WindowMoments {
+    count: usize,
+    mean_x: f64,
+    mean_y: f64,
+    m2_x: f64,
+    m2_y: f64,
+    co_moment: f64,
+}
+
+impl WindowMoments {
+    fn add(&mut self, x: f64, y: f64) {
+        self.count += 1;
+        let n = self.count as f64;
+
+        let delta_x = x - self.mean_x;
+        let delta_y = y - self.mean_y;
+
+        self.mean_x += delta_x / n;
+        self.mean_y += delta_y / n;
+        self.m2_x += delta_x * (x - self.mean_x);
+        self.m2_y += delta_y * (y - self.mean_y);
+        self.co_moment += delta_x * (y - self.mean_y);
+    }
+
+    fn remove(&mut self, x: f64, y: f64) {
+        if self.count == 1 {
+            *self = Self::default();
+            return;
+        }
+
+        self.count -= 1;
+        let n = self.count as f64;
+
+        let delta_x = x - self.mean_x;
+        let delta_y = y - self.mean_y;
+
+        self.mean_x -= delta_x / n;
+        self.mean_y -= delta_y / n;
+        self.m2_x -= delta_x * (x - self.mean_x);
+        self.m2_y -= delta_y * (y - self.mean_y);
+        self.co_moment -= delta_x * (y - self.mean_y);
+    }
+
+    fn sample_variance_x(&self) -> f64 {
+        // Guard against small negative values from floating-point cancellation.
+        self.m2_x.max(0.0) / (self.count - 1) as f64
+    }
+
+    fn sample_variance_y(&self) -> f64 {
+        self.m2_y.max(0.0) / (self.count - 1) as f64
+    }
+
+    fn sample_covariance(&self) -> f64 {
+        self.co_moment / (self.count - 1) as f64
+    }
+}
+
+/// Slide a window over paired values in O(n), mapping each window's moments to an output.
+///
+/// Outputs are aligned with the input and are NaN where the window has fewer than
+/// `window.min_periods` (or `required`) values or contains a NaN. NaNs are kept out of the
+/// running moments, so the outputs recover once they leave the window.
+fn roll<F: Fn(&WindowMoments) -> f64>(
+    x: &[f64],
+    y: &[f64],
+    window: Window,
+    required: usize,
+    f: F,
+) -> Vec<f64> {
+    let mut moments = WindowMoments::default();
+    let mut missing = 0;
+    let min_periods = window.min_periods.max(required);
+    let is_nan = |i: usize| x[i].is_nan() || y[i].is_nan();
+
+    (0..x.len().min(y.len()))
+        .map(|i| {
+            if is_nan(i) {
+                missing += 1;
+            } else {
+                moments.add(x[i], y[i]);
+            }
+
+            if i >= window.size {
+                let j = i - window.size;
+
+                if is_nan(j) {
+                    missing -= 1;
+                } else {
+                    moments.remove(x[j], y[j]);
+                }
+            }
+
+            if missing > 0 || moments.count < min_periods {
+                f64::NAN
+            } else {
+                f(&moments)
+            }
+        })
+        .collect()
+}
+
+/// Exponentially weighted average of a function of the values, seeded with the first value.
+///
+/// Outputs are NaN until `min_periods` values have been averaged. A NaN value is skipped, with
+/// a NaN output, so the average carries on from the values before it.
+fn exponentially_weighted<F: Fn(f64) -> f64>(
+    values: &[f64],
+    lambda: f64,
+    min_periods: usize,
+    f: F,
+) -> Vec<f64> {
+    let mut average = f64::NAN;
+    let mut count = 0;
+
+    values
+        .iter()
+        .map(|v| {
+            if v.is_nan() {
+                return f64::NAN;
+            }
+
+            average = if count == 0 {
+                f(*v)
+            } else {
+                lambda * average + (1.0 - lambda) * f(*v)
+            };
+            count += 1;
+
+            if count < min_periods {
+                f64::NAN
+            } else {
+                average
+            }
+        })
+        .collect()
+}
+
+/// Rolling-window and exponentially weighted versions of the `Statistics` methods
+pub trait RollingStatistics: Statistics {
+    /// Calculate the rolling mean
+    fn rolling_mean(&self, window: Window) -> Vec<f64> {
+        let values = self.values();
+        roll(values, values, window, 1, |m| m.mean_x)
+    }
+
+    /// Calculate the rolling sample variance
+    fn rolling_variance(&self, window: Window) -> Vec<f64> {
+        let values = self.values();
+        roll(values, values, window, 2, |m| m.sample_variance_x())
+    }
+
+    /// Calculate the rolling sample standard deviation
+    fn rolling_std_dev(&self, window: Window) -> Vec<f64> {
+        let values = self.values();
+        roll(values, values, window, 2, |m| m.sample_variance_x().sqrt())
+    }
+
+    /// Calculate the rolling standard deviation, annualized by the number of periods per year
+    fn rolling_volatility(&self, window: Window, periods_per_year: f64) -> Vec<f64> {
+        self.rolling_std_dev(window)
+            .into_iter()
+            .map(|v| v * periods_per_year.sqrt())
+            .collect()
+    }
+
+    /// Calculate the rolling Sharpe Ratio
+    fn rolling_sharpe_ratio(&self, window: Window, risk_free_rate: f64) -> Vec<f64> {
+        let values = self.values();
+        roll(values, values, window, 2, |m| {
+            (m.mean_x - risk_free_rate) / m.sample_variance_x().sqrt()
+        })
+    }
+
+    /// Calculate the rolling sample covariance with another distribution
+    fn rolling_covariance(&self, other: &Self, window: Window) -> Vec<f64> {
+        roll(self.values(), other.values(), window, 2, |m| {
+            m.sample_covariance()
+        })
+    }
+
+    /// Calculate the rolling correlation with another distribution
+    fn rolling_correlation(&self, other: &Self, window: Window) -> Vec<f64> {
+        roll(self.values(), other.values(), window, 2, |m| {
+            m.sample_covariance() / (m.sample_variance_x() * m.sample_variance_y()).sqrt()
+        })
+    }
+
+    /// Calculate the exponentially weighted moving average, seeded with the first value
+    fn ewma_mean(&self, lambda: f64, min_periods: usize) -> Vec<f64> {
+        exponentially_weighted(self.values(), lambda, min_periods, |v| v)
+    }
+
+    /// Calculate the RiskMetrics EWMA variance, which assumes a zero mean return
+    fn ewma_variance(&self, lambda: f64, min_periods: usize) -> Vec<f64> {
+        exponentially_weighted(self.values(), lambda, min_periods, |v| v * v)
+    }
+
+    /// Calculate the RiskMetrics EWMA standard deviation
+    fn ewma_volatility(&self, lambda: f64, min_periods: usize) -> Vec<f64> {
+        self.ewma_variance(lambda, min_periods)
+            .into_iter()
+            .map(f64::sqrt)
+            .collect()
+    }
+}
+
+impl<T: Statistics + ?Sized> RollingStatistics for T {}
@@ -0,0 +1,269 @@
+/// Error type for scenario analysis
+#[derive(Debug)]
+pub enum Error {
+    /// Probabilities must be between 0 and 1
+    InvalidProbability(f64),
+    /// Probabilities of mutually exclusive and exhaustive events must sum to one
+    ProbabilitiesDoNotSumToOne(f64),
+    /// No scenario exists with the given label
+    UnknownScenario(String),
+    /// No outcome exists with the given label
+    UnknownOutcome(String),
+    /// Cannot condition on an event with zero probability
+    ZeroProbabilityEvent,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Tolerance used when checking that probabilities sum to one
+const PROBABILITY_TOLERANCE: f64 = 1e-9;
+
+/// Calculate the posterior probability of an event given new information with Bayes' formula
+///
+/// `P(E|I) = P(I|E) / P(I) * P(E)`
+pub fn bayes(prior: f64, likelihood: f64, evidence: f64) -> Result<f64, Error> {
+    if evidence == 0.0 {
+        return Err(Error::ZeroProbabilityEvent);
+    }
+
+    Ok(likelihood / evidence * prior)
+}
+
+/// A labelled value of a random variable and its probability
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub label: String,
+    pub probability: f64,
+    pub value: f64,
+}
+
+impl Outcome {
+    pub fn new(label: &str, probability: f64, value: f64) -> Self {
+        Self {
+            label: label.to_string(),
+            probability,
+            value,
+        }
+    }
+}
+
+/// Ensure probabilities are valid and sum to one
+fn check_probabilities<'a, I: Iterator<Item = &'a f64>>(probabilities: I) -> Result<(), Error> {
+    let mut total = 0.0;
+
+    for p in probabilities {
+        if !(0.0..=1.0).contains(p) {
+            return Err(Error::InvalidProbability(*p));
+        }
+
+        total += p;
+    }
+
+    if (total - 1.0).abs() > PROBABILITY_TOLERANCE {
+        return Err(Error::ProbabilitiesDoNotSumToOne(total));
+    }
+
+    Ok(())
+}
+
+/// A discrete random variable over mutually exclusive and exhaustive outcomes
+#[derive(Debug, Clone)]
+pub struct RandomVariable {
+    pub outcomes: Vec<Outcome>,
+}
+
+impl RandomVariable {
+    pub fn new(outcomes: Vec<Outcome>) -> Result<Self, Error> {
+        check_probabilities(outcomes.iter().map(|o| &o.probability))?;
+
+        Ok(Self { outcomes })
+    }
+
+    /// Calculate the expected value `E(X)`
+    pub fn expected_value(&self) -> f64 {
+        self.outcomes.iter().map(|o| o.probability * o.value).sum()
+    }
+
+    /// Calculate the variance of the random variable
+    pub fn variance(&self) -> f64 {
+        let expected_value = self.expected_value();
+
+        self.outcomes
+            .iter()
+            .map(|o| o.probability * (o.value - expected_value).powi(2))
+            .sum()
+    }
+
+    /// Calculate the standard deviation of the random variable
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Return the probability of an outcome
+    pub fn probability(&self, label: &str) -> Result<f64, Error> {
+        self.outcomes
+            .iter()
+            .find(|o| o.label == label)
+            .map(|o| o.probability)
+            .ok_or_else(|| Error::UnknownOutcome(label.to_string()))
+    }
+}
+
+/// A scenario with its probability and the outcomes conditional on it occurring
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub label: String,
+    /// Unconditional probability of the scenario, `P(S)`
+    pub probability: f64,
+    /// Outcomes with probabilities conditional on the scenario, `P(X|S)`
+    pub outcomes: RandomVariable,
+}
+
+impl Scenario {
+    pub fn new(label: &str, probability: f64, outcomes: Vec<Outcome>) -> Result<Self, Error> {
+        if !(0.0..=1.0).contains(&probability) {
+            return Err(Error::InvalidProbability(probability));
+        }
+
+        Ok(Self {
+            label: label.to_string(),
+            probability,
+            outcomes: RandomVariable::new(outcomes)?,
+        })
+    }
+
+    /// Conditional expected value of the outcomes given the scenario, `E(X|S)`
+    pub fn conditional_expected_value(&self) -> f64 {
+        self.outcomes.expected_value()
+    }
+}
+
+/// Mutually exclusive and exhaustive scenarios, each branching into conditional outcomes
+#[derive(Debug, Clone)]
+pub struct ScenarioTree {
+    pub scenarios: Vec<Scenario>,
+}
+
+impl ScenarioTree {
+    pub fn new(scenarios: Vec<Scenario>) -> Result<Self, Error> {
+        check_probabilities(scenarios.iter().map(|s| &s.probability))?;
+
+        Ok(Self { scenarios })
+    }
+
+    /// Return a scenario by label
+    pub fn scenario(&self, label: &str) -> Result<&Scenario, Error> {
+        self.scenarios
+            .iter()
+            .find(|s| s.label == label)
+            .ok_or_else(|| Error::UnknownScenario(label.to_string()))
+    }
+
+    /// Conditional expected value given a scenario, `E(X|S)`
+    pub fn conditional_expected_value(&self, scenario: &str) -> Result<f64, Error> {
+        Ok(self.scenario(scenario)?.conditional_expected_value())
+    }
+
+    /// Expected value with the total probability rule, `E(X) = sum E(X|S_i) P(S_i)`
+    pub fn expected_value(&self) -> f64 {
+        self.scenarios
+            .iter()
+            .map(|s| s.conditional_expected_value() * s.probability)
+            .sum()
+    }
+
+    /// Unconditional variance of the outcomes across all scenarios
+    pub fn variance(&self) -> f64 {
+        let expected_value = self.expected_value();
+
+        self.scenarios
+            .iter()
+            .flat_map(|s| {
+                s.outcomes.outcomes.iter().map(move |o| {
+                    s.probability * o.probability * (o.value - expected_value).powi(2)
+                })
+            })
+            .sum()
+    }
+
+    /// Unconditional standard deviation of the outcomes across all scenarios
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Collapse the tree into a single random variable of joint scenario and outcome probabilities
+    pub fn joint_outcomes(&self) -> RandomVariable {
+        RandomVariable {
+            outcomes: self
+                .scenarios
+                .iter()
+                .flat_map(|s| {
+                    s.outcomes.outcomes.iter().map(move |o| Outcome {
+                        label: format!("{}/{}", s.label, o.label),
+                        probability: s.probability * o.probability,
+                        value: o.value,
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// Unconditional probability of an outcome with the total probability rule,
+    /// `P(O) = sum P(O|S_i) P(S_i)`
+    ///
+    /// Scenarios without an outcome of the given label contribute zero probability.
+    pub fn total_probability(&self, outcome: &str) -> Result<f64, Error> {
+        let mut found = false;
+        let mut probability = 0.0;
+
+        for scenario in &self.scenarios {
+            if let Ok(p) = scenario.outcomes.probability(outcome) {
+                found = true;
+                probability += p * scenario.probability;
+            }
+        }
+
+        if !found {
+            return Err(Error::UnknownOutcome(outcome.to_string()));
+        }
+
+        Ok(probability)
+    }
+
+    /// Posterior probability of each scenario after observing an outcome, `P(S_i|O)`
+    pub fn posterior(&self, outcome: &str) -> Result<Vec<(String, f64)>, Error> {
+        let evidence = self.total_probability(outcome)?;
+
+        self.scenarios
+            .iter()
+            .map(|s| {
+                let likelihood = s.outcomes.probability(outcome).unwrap_or(0.0);
+
+                Ok((s.label.clone(), bayes(s.probability, likelihood, evidence)?))
+            })
+            .collect()
+    }
+
+    /// Return a new tree with scenario probabilities updated for an observed outcome
+    pub fn update(&self, outcome: &str) -> Result<Self, Error> {
+        let posterior = self.posterior(outcome)?;
+
+        Ok(Self {
+            scenarios: self
+                .scenarios
+                .iter()
+                .zip(posterior)
+                .map(|(s, (_, p))| Scenario {
+                    probability: p,
+                    ..s.clone()
+                })
+                .collect(),
+        })
+    }
+}
@@ -1,254 +1,722 @@
+use crate::quantile::{self, QuantileMethod};
+
+/// Error type for statistical calculations
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatsError {
+    /// The distribution has no values
+    EmptyInput,
+    /// The calculation requires more values than the distribution has
+    InsufficientData { required: usize, actual: usize },
+    /// Two inputs that must be the same length are not
+    LengthMismatch { expected: usize, actual: usize },
+    /// The distribution contains a NaN value
+    NotANumber,
+    /// Weights must sum to one
+    InvalidWeights,
+    /// Percentiles must be between 0 and 100
+    InvalidPercentile(f64),
+    /// A value is outside of the domain of the calculation, e.g. zero for the harmonic mean
+    InvalidDomain,
+    /// The calculation divides by a variance (or mean) of zero
+    DegenerateVariance,
+}
+
+impl std::fmt::Display for StatsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for StatsError {}
+
+/// Number of trading days in a year, used to annualize daily statistics
+pub const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+
+/// Tolerance used when checking that weights sum to one
+const WEIGHT_TOLERANCE: f64 = 1e-9;
+
+/// Streaming (Welford-style) accumulator of the moments of a distribution.
+///
+/// Values are added one at a time without storing history, and accumulators built
+/// over separate chunks or threads can be merged.
+#[derive(Debug, Clone)]
+pub struct RunningStats {
+    /// Number of values
+    count: usize,
+    /// Mean of the values
+    mean: f64,
+    /// Sum of squared deviations from the mean
+    m2: f64,
+    /// Sum of cubed deviations from the mean
+    m3: f64,
+    /// Sum of fourth-power deviations from the mean
+    m4: f64,
+    /// Smallest value
+    min: f64,
+    /// Largest value
+    max: f64,
+}
+
+impl Default for RunningStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunningStats {
+    /// Create an empty accumulator
+    pub fn new() -> Self {
+        Self {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            m3: 0.0,
+            m4: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Accumulate the moments of a slice of values
+    pub fn from_values(values: &[f64]) -> Self {
+        values.iter().copied().collect()
+    }
+
+    /// Add a value, updating the moments in place
+    pub fn push(&mut self, value: f64) {
+        let n1 = self.count as f64;
+        self.count += 1;
+        let n = self.count as f64;
+
+        let delta = value - self.mean;
+        let delta_n = delta / n;
+        let delta_n2 = delta_n * delta_n;
+        let term = delta * delta_n * n1;
+
+        self.mean += delta_n;
+        self.m4 += term * delta_n2 * (n * n - 3.0 * n + 3.0) + 6.0 * delta_n2 * self.m2
+            - 4.0 * delta_n * self.m3;
+        self.m3 += term * delta_n * (n - 2.0) - 3.0 * delta_n * self.m2;
+        self.m2 += term;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Combine the moments of another accumulator into this one
+    pub fn merge(&mut self, other: &Self) {
+        if other.count == 0 {
+            return;
+        }
+
+        if self.count == 0 {
+            *self = other.clone();
+            return;
+        }
+
+        let na = self.count as f64;
+        let nb = other.count as f64;
+        let n = na + nb;
+
+        let delta = other.mean - self.mean;
+        let delta2 = delta * delta;
+        let delta3 = delta2 * delta;
+        let delta4 = delta2 * delta2;
+
+        let m2 = self.m2 + other.m2 + delta2 * na * nb / n;
+        let m3 = self.m3
+            + other.m3
+            + delta3 * na * nb * (na - nb) / (n * n)
+            + 3.0 * delta * (na * other.m2 - nb * self.m2) / n;
+        let m4 = self.m4
+            + other.m4
+            + delta4 * na * nb * (na * na - na * nb + nb * nb) / (n * n * n)
+            + 6.0 * delta2 * (na * na * other.m2 + nb * nb * self.m2) / (n * n)
+            + 4.0 * delta * (na * other.m3 - nb * self.m3) / n;
+
+        self.count += other.count;
+        self.mean += delta * nb / n;
+        self.m2 = m2;
+        self.m3 = m3;
+        self.m4 = m4;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// Number of values added
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Mean of the values, NaN when empty
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            return f64::NAN;
+        }
+
+        self.mean
+    }
+
+    /// Smallest value, NaN when empty
+    pub fn min(&self) -> f64 {
+        if self.count == 0 {
+            return f64::NAN;
+        }
+
+        self.min
+    }
+
+    /// Largest value, NaN when empty
+    pub fn max(&self) -> f64 {
+        if self.count == 0 {
+            return f64::NAN;
+        }
+
+        self.max
+    }
+
+    /// Population variance of the values
+    pub fn population_variance(&self) -> f64 {
+        self.m2 / self.count as f64
+    }
+
+    /// Sample variance of the values
+    pub fn sample_variance(&self) -> f64 {
+        self.m2 / (self.count as f64 - 1.0)
+    }
+
+    /// Sample standard deviation of the values
+    pub fn sample_std_dev(&self) -> f64 {
+        self.sample_variance().sqrt()
+    }
+
+    /// Skewness of the values, matching `Statistics::skewness`
+    pub fn skewness(&self) -> f64 {
+        self.m3 / self.count as f64 / self.sample_std_dev().powf(3.)
+    }
+
+    /// Excess kurtosis of the values, matching `Statistics::excess_kurtosis`
+    pub fn excess_kurtosis(&self) -> f64 {
+        self.m4 / self.count as f64 / self.sample_std_dev().powf(4.) - 3.0
+    }
+}
+
+impl FromIterator<f64> for RunningStats {
+    fn from_iter<I: IntoIterator<Item = f64>>(iter: I) -> Self {
+        let mut stats = Self::new();
+        stats.extend(iter);
+        stats
+    }
+}
+
+impl Extend<f64> for RunningStats {
+    fn extend<I: IntoIterator<Item = f64>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+/// Streaming accumulator of the covariance between two paired series
+#[derive(Debug, Clone, Default)]
+pub struct RunningCovariance {
+    /// Moments of the first series
+    x: RunningStats,
+    /// Moments of the second series
+    y: RunningStats,
+    /// Sum of the products of the deviations from each mean
+    co_moment: f64,
+}
+
+impl RunningCovariance {
+    /// Create an empty accumulator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accumulate the co-moment of two paired slices, truncated to the shorter
+    pub fn from_values(x: &[f64], y: &[f64]) -> Self {
+        let mut covariance = Self::new();
+
+        for (a, b) in x.iter().zip(y.iter()) {
+            covariance.push(*a, *b);
+        }
+
+        covariance
+    }
+
+    /// Add a pair of values, updating the co-moment in place
+    pub fn push(&mut self, x: f64, y: f64) {
+        let delta_x = x - self.x.mean;
+
+        self.x.push(x);
+        self.y.push(y);
+
+        self.co_moment += delta_x * (y - self.y.mean);
+    }
+
+    /// Combine the co-moment of another accumulator into this one
+    pub fn merge(&mut self, other: &Self) {
+        let na = self.x.count as f64;
+        let nb = other.x.count as f64;
+
+        if na + nb > 0.0 {
+            self.co_moment += other.co_moment
+                + (other.x.mean - self.x.mean) * (other.y.mean - self.y.mean) * na * nb / (na + nb);
+        }
+
+        self.x.merge(&other.x);
+        self.y.merge(&other.y);
+    }
+
+    /// Number of pairs added
+    pub fn count(&self) -> usize {
+        self.x.count
+    }
+
+    /// Moments of the first series
+    pub fn x(&self) -> &RunningStats {
+        &self.x
+    }
+
+    /// Moments of the second series
+    pub fn y(&self) -> &RunningStats {
+        &self.y
+    }
+
+    /// Population covariance of the pairs
+    pub fn population_covariance(&self) -> f64 {
+        self.co_moment / self.count() as f64
+    }
+
+    /// Sample covariance of the pairs, matching `Statistics::covariance`
+    pub fn sample_covariance(&self) -> f64 {
+        self.co_moment / (self.count() as f64 - 1.0)
+    }
+
+    /// Pearson correlation of the pairs, matching `Statistics::correlation`
+    pub fn correlation(&self) -> f64 {
+        self.sample_covariance() / (self.x.sample_std_dev() * self.y.sample_std_dev())
+    }
+}
+
 pub trait Statistics {
-    /// Return a set of floating point 64-bit values
-    fn values(&self) -> Vec<f64>;
+    /// Return a slice of floating point 64-bit values
+    fn values(&self) -> &[f64];
 
     /// Return the count of values
     fn count(&self) -> usize {
         self.values().len()
     }
 
+    /// Return the values, ensuring there are at least `required` of them and none are NaN
+    fn try_values(&self, required: usize) -> Result<&[f64], StatsError> {
+        let values = self.values();
+
+        if values.is_empty() {
+            return Err(StatsError::EmptyInput);
+        }
+
+        if values.len() < required {
+            return Err(StatsError::InsufficientData {
+                required,
+                actual: values.len(),
+            });
+        }
+
+        if values.iter().any(|v| v.is_nan()) {
+            return Err(StatsError::NotANumber);
+        }
+
+        Ok(values)
+    }
+
+    /// Calculate the central moments, ensuring there are at least `required` values
+    fn try_moments(&self, required: usize) -> Result<RunningStats, StatsError> {
+        Ok(RunningStats::from_values(self.try_values(required)?))
+    }
+
     /// Calculate the population mean
-    fn population_mean(&self) -> f64 {
-        self.values().iter().sum::<f64>() / self.count() as f64
+    fn try_population_mean(&self) -> Result<f64, StatsError> {
+        Ok(self.try_moments(1)?.mean)
     }
 
     /// Calculate the sample mean
-    fn sample_mean(&self) -> f64 {
-        self.values().iter().sum::<f64>() / (self.count() - 1) as f64
+    fn try_sample_mean(&self) -> Result<f64, StatsError> {
+        self.try_population_mean()
     }
 
     /// Calculate the geometric mean
-    fn geometric_mean(&self) -> f64 {
-        self.values()
+    fn try_geometric_mean(&self) -> Result<f64, StatsError> {
+        let values = self.try_values(1)?;
+
+        // Returns of -100% or worse have no geometric mean.
+        if values.iter().any(|v| *v <= -1.0) {
+            return Err(StatsError::InvalidDomain);
+        }
+
+        Ok(values
             .iter()
             .map(|v| 1.0 + v)
             .product::<f64>()
-            .powf(1.0 / self.count() as f64)
-            - 1.
+            .powf(1.0 / values.len() as f64)
+            - 1.)
     }
 
     /// Calculate the weighted average
-    fn weighted_average(&self, weights: Vec<f64>) -> f64 {
-        if self.count() != weights.len() {
-            return 0.0;
+    fn try_weighted_average(&self, weights: Vec<f64>) -> Result<f64, StatsError> {
+        let values = self.try_values(1)?;
+
+        if values.len() != weights.len() {
+            return Err(StatsError::LengthMismatch {
+                expected: values.len(),
+                actual: weights.len(),
+            });
         }
 
-        if weights.iter().sum::<f64>() != 1.0 {
-            return 0.0;
+        if (weights.iter().sum::<f64>() - 1.0).abs() > WEIGHT_TOLERANCE {
+            return Err(StatsError::InvalidWeights);
         }
 
-        self.values()
+        Ok(values
             .iter()
             .zip(weights.iter())
             .map(|(v, w)| v * w)
-            .sum::<f64>()
+            .sum::<f64>())
     }
 
     /// Calculate the harmonic mean
-    fn harmonic_mean(&self) -> f64 {
-        self.values().iter().map(|v| 1.0 / v).sum::<f64>().recip() / self.count() as f64
+    fn try_harmonic_mean(&self) -> Result<f64, StatsError> {
+        let values = self.try_values(1)?;
+
+        if values.contains(&0.0) {
+            return Err(StatsError::InvalidDomain);
+        }
+
+        Ok(values.len() as f64 / values.iter().map(|v| 1.0 / v).sum::<f64>())
     }
 
     /// Calculate the percentile of a distribution
-    fn percentile(&self, target_percentile: f64) -> f64 {
-        let mut values = self.values();
+    fn try_percentile(&self, target_percentile: f64) -> Result<f64, StatsError> {
+        if !(0.0..=100.0).contains(&target_percentile) {
+            return Err(StatsError::InvalidPercentile(target_percentile));
+        }
+
+        let mut values = self.try_values(1)?.to_vec();
         // Sorted our values in ascending order
-        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        values.sort_by(|a, b| a.total_cmp(b));
 
-        let index = (self.count() + 1) as f64 * (target_percentile / 100.0);
+        // The `(n + 1)p` index is Hyndman and Fan's sixth definition.
+        quantile::quantile_sorted(&values, target_percentile / 100.0, QuantileMethod::Type6)
+    }
 
-        if index.fract() == 0.0 {
-            return values[index as usize];
-        } else {
-            let index_floor = index.floor();
-            let index_ceil = index.ceil();
+    /// Calculate the Range of the distribution
+    fn try_range(&self) -> Result<f64, StatsError> {
+        let moments = self.try_moments(1)?;
 
-            let value_floor = values[index_floor as usize];
-            let value_ceil = values[index_ceil as usize];
+        Ok(moments.max - moments.min)
+    }
 
-            let value = value_floor + (index - index_floor) * (value_ceil - value_floor);
+    /// Return the largest value
+    fn try_max(&self) -> Result<f64, StatsError> {
+        Ok(self.try_moments(1)?.max)
+    }
 
-            return value;
-        }
+    /// Return the smallest value
+    fn try_min(&self) -> Result<f64, StatsError> {
+        Ok(self.try_moments(1)?.min)
     }
 
-    /// Calculate the Range of the distribution
-    fn range(&self) -> f64 {
-        self.max() - self.min()
+    /// Calculate population variance
+    fn try_population_variance(&self) -> Result<f64, StatsError> {
+        Ok(self.try_moments(1)?.population_variance())
     }
 
-    fn max(&self) -> f64 {
-        let mut value = 0.0;
+    /// Calculate sample variance
+    fn try_sample_variance(&self) -> Result<f64, StatsError> {
+        Ok(self.try_moments(2)?.sample_variance())
+    }
+
+    /// Calculate population standard deviation
+    fn try_population_std_dev(&self) -> Result<f64, StatsError> {
+        Ok(self.try_population_variance()?.sqrt())
+    }
+
+    /// Calculate sample standard deviation
+    fn try_sample_std_dev(&self) -> Result<f64, StatsError> {
+        Ok(self.try_sample_variance()?.sqrt())
+    }
+
+    /// Calculate sample standard deviation, failing if it is zero
+    fn try_nonzero_std_dev(&self) -> Result<f64, StatsError> {
+        nonzero_std_dev(&self.try_moments(2)?)
+    }
+
+    /// Calculate downside deviation (semideviation)
+    fn try_downside_deviation(&self) -> Result<f64, StatsError> {
+        let values = self.try_values(2)?;
+        let mean = RunningStats::from_values(values).mean;
+
+        let variance = values
+            .iter()
+            .filter(|v| **v <= mean)
+            .map(|v| (v - mean).powf(2.))
+            .sum::<f64>()
+            / (values.len() - 1) as f64;
+
+        Ok(variance.sqrt())
+    }
 
-        for v in self.values() {
-            if v > value {
-                value = v;
-            }
+    /// Calculate Coefficience of Variance
+    fn try_coefficient_variance(&self) -> Result<f64, StatsError> {
+        let moments = self.try_moments(2)?;
+
+        if moments.mean == 0.0 {
+            return Err(StatsError::DegenerateVariance);
         }
 
-        value
+        Ok(moments.sample_variance().sqrt() / moments.mean)
     }
 
-    fn min(&self) -> f64 {
-        let mut value = 0.0;
+    /// Calculate the Sharpe Ratio
+    fn try_sharpe_ratio(&self, risk_free_rate: f64) -> Result<f64, StatsError> {
+        let moments = self.try_moments(2)?;
+
+        Ok((moments.mean - risk_free_rate) / nonzero_std_dev(&moments)?)
+    }
+
+    /// Normalize the values as z-scores
+    fn try_z_scores(&self) -> Result<Vec<f64>, StatsError> {
+        let moments = self.try_moments(2)?;
+        let std_dev = nonzero_std_dev(&moments)?;
+
+        Ok(self
+            .values()
+            .iter()
+            .map(|v| (v - moments.mean) / std_dev)
+            .collect::<Vec<f64>>())
+    }
+
+    /// Calculate the skewness of the distribution
+    fn try_skewness(&self) -> Result<f64, StatsError> {
+        let moments = self.try_moments(2)?;
+        let std_dev = nonzero_std_dev(&moments)?;
+
+        Ok(moments.m3 / moments.count as f64 / std_dev.powf(3.))
+    }
 
-        for v in self.values() {
-            if v < value {
-                value = v;
-            }
+    /// Calculate the Excess Kurtosis
+    fn try_excess_kurtosis(&self) -> Result<f64, StatsError> {
+        let moments = self.try_moments(2)?;
+        let std_dev = nonzero_std_dev(&moments)?;
+
+        Ok(moments.m4 / moments.count as f64 / std_dev.powf(4.)
+            // Subject 3 becuase kurtosis of a normal distribution is 3
+            - 3.0)
+    }
+
+    /// Calculate Covariance with another distribution
+    fn try_covariance(&self, other: &Self) -> Result<f64, StatsError> {
+        let self_values = self.try_values(2)?;
+        let other_values = other.try_values(2)?;
+
+        if self_values.len() != other_values.len() {
+            return Err(StatsError::LengthMismatch {
+                expected: self_values.len(),
+                actual: other_values.len(),
+            });
         }
 
-        value
+        Ok(RunningCovariance::from_values(self_values, other_values).sample_covariance())
     }
 
-    /// Calculate population variance
-    fn population_variance(&self) -> f64 {
-        let mean = self.expected_probability();
+    /// Calculate Correlation with another distribution
+    fn try_correlation(&self, other: &Self) -> Result<f64, StatsError> {
+        let covariance = self.try_covariance(other)?;
+
+        Ok(covariance / (self.try_nonzero_std_dev()? * other.try_nonzero_std_dev()?))
+    }
+
+    /// Calculate the probability of a value occurring in the distribution
+    fn try_probability(&self, value: &f64) -> Result<f64, StatsError> {
+        let values = self.try_values(1)?;
 
-        self.values()
+        Ok(values.iter().filter(|r| *r == value).count() as f64 / values.len() as f64)
+    }
+
+    /// Calculate the probability of a value falling within the bounds (inclusive)
+    fn try_probability_bounds(
+        &self,
+        lower_bound: f64,
+        upper_bound: f64,
+    ) -> Result<f64, StatsError> {
+        let values = self.try_values(1)?;
+
+        Ok(values
             .iter()
-            .map(|v| (v - mean).powf(2.))
-            .sum::<f64>()
-            / self.count() as f64
+            .filter(|r| *r >= &lower_bound && *r <= &upper_bound)
+            .count() as f64
+            / values.len() as f64)
+    }
+
+    /// Calculate the population mean
+    fn population_mean(&self) -> f64 {
+        self.try_population_mean().unwrap_or(f64::NAN)
+    }
+
+    /// Calculate the sample mean
+    fn sample_mean(&self) -> f64 {
+        self.try_sample_mean().unwrap_or(f64::NAN)
+    }
+
+    /// Calculate the geometric mean
+    fn geometric_mean(&self) -> f64 {
+        self.try_geometric_mean().unwrap_or(f64::NAN)
+    }
+
+    /// Calculate the weighted average
+    fn weighted_average(&self, weights: Vec<f64>) -> f64 {
+        self.try_weighted_average(weights).unwrap_or(f64::NAN)
+    }
+
+    /// Calculate the harmonic mean
+    fn harmonic_mean(&self) -> f64 {
+        self.try_harmonic_mean().unwrap_or(f64::NAN)
+    }
+
+    /// Calculate the percentile of a distribution
+    fn percentile(&self, target_percentile: f64) -> f64 {
+        self.try_percentile(target_percentile).unwrap_or(f64::NAN)
+    }
+
+    /// Calculate the Range of the distribution
+    fn range(&self) -> f64 {
+        self.try_range().unwrap_or(f64::NAN)
+    }
+
+    fn max(&self) -> f64 {
+        self.try_max().unwrap_or(f64::NAN)
+    }
+
+    fn min(&self) -> f64 {
+        self.try_min().unwrap_or(f64::NAN)
+    }
+
+    /// Calculate population variance
+    fn population_variance(&self) -> f64 {
+        self.try_population_variance().unwrap_or(f64::NAN)
     }
 
     /// Calculate sample variance
     fn sample_variance(&self) -> f64 {
-        let mean = self.expected_probability();
-
-        self.values()
-            .iter()
-            .map(|v| (v - mean).powf(2.))
-            .sum::<f64>()
-            / (self.count() - 1) as f64
+        self.try_sample_variance().unwrap_or(f64::NAN)
     }
 
     /// Calculate population standard deviation
     fn population_std_dev(&self) -> f64 {
-        self.population_variance().sqrt()
+        self.try_population_std_dev().unwrap_or(f64::NAN)
     }
 
     /// Calculate sample standard deviation
     fn sample_std_dev(&self) -> f64 {
-        self.sample_variance().sqrt()
+        self.try_sample_std_dev().unwrap_or(f64::NAN)
     }
 
     /// Calculate downside deviation (semideviation)
     fn downside_deviation(&self) -> f64 {
-        let mean = self.expected_probability();
-
-        let variance = self
-            .values()
-            .iter()
-            .filter(|v| **v <= mean)
-            .map(|v| (v - mean).powf(2.))
-            .sum::<f64>()
-            / (self.count() - 1) as f64;
-
-        variance.sqrt()
+        self.try_downside_deviation().unwrap_or(f64::NAN)
     }
 
     /// Calculate Coefficience of Variance
     fn coefficient_variance(&self) -> f64 {
-        self.sample_std_dev() / self.expected_probability()
+        self.try_coefficient_variance().unwrap_or(f64::NAN)
     }
 
     /// Calculate the Sharpe Ratio
     fn sharpe_ratio(&self, risk_free_rate: f64) -> f64 {
-        (self.expected_probability() - risk_free_rate) / self.sample_std_dev()
+        self.try_sharpe_ratio(risk_free_rate).unwrap_or(f64::NAN)
     }
 
     /// Normalize the values as z-scores
     fn z_scores(&self) -> Vec<f64> {
-        let mean = self.expected_probability();
-        let std_dev = self.sample_std_dev();
-
-        self.values()
-            .iter()
-            .map(|v| v - mean / std_dev)
-            .collect::<Vec<f64>>()
+        self.try_z_scores()
+            .unwrap_or_else(|_| vec![f64::NAN; self.count()])
     }
 
     /// Calculate the skewness of the distribution
     fn skewness(&self) -> f64 {
-        let mean = self.expected_probability();
-
-        (1 / self.count()) as f64
-            * self
-                .values()
-                .iter()
-                .map(|v| (v - mean).powf(3.))
-                .sum::<f64>()
-            / self.sample_std_dev().powf(3.)
+        self.try_skewness().unwrap_or(f64::NAN)
     }
 
     /// Calculate the Excess Kurtosis
     fn excess_kurtosis(&self) -> f64 {
-        let mean = self.expected_probability();
-
-        (1 / self.count()) as f64
-            * self
-                .values()
-                .iter()
-                .map(|v| (v - mean).powf(4.))
-                .sum::<f64>()
-            / self.sample_std_dev().powf(4.)
-            // Subject 3 becuase kurtosis of a normal distribution is 3
-            - 3.0
+        self.try_excess_kurtosis().unwrap_or(f64::NAN)
     }
 
     /// Calculate Covariance with another distribution
     fn covariance(&self, other: &Self) -> f64 {
-        let self_mean = self.expected_probability();
-        let other_mean = other.expected_probability();
-        let n = (self.count() - 1) as f64;
-
-        self.values()
-            .iter()
-            .zip(other.values().iter())
-            .map(|(a, b)| (a - self_mean) * (b - other_mean))
-            .sum::<f64>()
-            / n
+        self.try_covariance(other).unwrap_or(f64::NAN)
     }
 
     /// Calculate Correlation with another distribution
     fn correlation(&self, other: &Self) -> f64 {
-        let covariance = self.covariance(other);
-        let self_std_dev = self.sample_std_dev();
-        let other_std_dev = other.sample_std_dev();
-
-        covariance / (self_std_dev * other_std_dev)
+        self.try_correlation(other).unwrap_or(f64::NAN)
     }
 
     fn probability(&self, value: &f64) -> f64 {
-        self.values().iter().filter(|r| r == &value).count() as f64 / self.values().len() as f64
+        self.try_probability(value).unwrap_or(f64::NAN)
     }
 
     fn probability_bounds(&self, lower_bound: f64, upper_bound: f64) -> f64 {
-        self.values()
-            .iter()
-            .filter(|r| *r >= &lower_bound && *r <= &upper_bound)
-            .count() as f64
-            / self.values().len() as f64
+        self.try_probability_bounds(lower_bound, upper_bound)
+            .unwrap_or(f64::NAN)
     }
 
     /// Weighted-average probability
     fn expected_probability(&self) -> f64 {
-        let mut expected_p = 0.0;
+        let values = self.values();
+
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
 
         let mantissa = 10_f64.powf(5.);
+        let n = values.len() as f64;
+
+        values
+            .iter()
+            .map(|target_return| {
+                let lower_bound = ((target_return * mantissa).floor()) / mantissa;
+                let upper_bound = ((target_return * mantissa).ceil()) / mantissa;
 
-        for target_return in self.values().iter() {
-            let lower_bound = ((target_return * mantissa).floor()) / mantissa;
-            let upper_bound = ((target_return * mantissa).ceil()) / mantissa;
+                // Count the values within the bounds with a binary search of the sorted values.
+                let count = sorted.partition_point(|v| *v <= upper_bound)
+                    - sorted.partition_point(|v| *v < lower_bound);
 
-            let prob = self.probability_bounds(lower_bound, upper_bound);
+                count as f64 / n * target_return
+            })
+            .sum()
+    }
+}
 
-            let value = prob * target_return;
-            expected_p += value;
-        }
+/// Sample standard deviation of the moments, failing if it is zero
+fn nonzero_std_dev(moments: &RunningStats) -> Result<f64, StatsError> {
+    let std_dev = moments.sample_variance().sqrt();
+
+    if std_dev == 0.0 {
+        return Err(StatsError::DegenerateVariance);
+    }
+
+    Ok(std_dev)
+}
+
+impl Statistics for [f64] {
+    fn values(&self) -> &[f64] {
+        self
+    }
+}
 
-        expected_p
+impl Statistics for Vec<f64> {
+    fn values(&self) -> &[f64] {
+        self
     }
 }
 
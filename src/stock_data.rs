@@ -1,7 +1,7 @@
-use std::{collections::HashMap, f32::consts::E};
+use std::collections::BTreeMap;
 
 use crate::{portfolio::Ticker, stats::Statistics};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -94,15 +94,12 @@ impl StockData {
     pub fn returns(&self) -> Returns {
         let mut values = vec![];
 
-        let mut current = 0.0;
-        let mut prior = 0.0;
-
-        for (i, v) in self.data.iter().enumerate() {
+        for i in 0..self.data.len() {
             if i == 0 {
                 values.push(0.0)
             } else {
-                current = self.data[i].close;
-                prior = self.data[i - 1].close;
+                let current = self.data[i].close;
+                let prior = self.data[i - 1].close;
                 let dividend = self.data[i].dividend;
 
                 let hpy = (current - prior + dividend) / prior;
@@ -113,10 +110,40 @@ impl StockData {
 
         Returns(values)
     }
+
+    /// Return the closing prices in the order of the bars
+    pub fn closing_prices(&self) -> Vec<f64> {
+        self.data.iter().map(|d| d.close).collect()
+    }
+
+    /// Return the dated dividend payments, excluding bars without a dividend
+    pub fn dividends(&self) -> Vec<(DateTime<Utc>, f64)> {
+        let mut dividends = self
+            .data
+            .iter()
+            .filter(|d| d.dividend > 0.0)
+            .map(|d| (d.date, d.dividend))
+            .collect::<Vec<_>>();
+
+        dividends.sort_by_key(|(date, _)| *date);
+
+        dividends
+    }
+
+    /// Return the total dividends paid in each calendar year, in ascending order
+    pub fn annual_dividends(&self) -> Vec<(i32, f64)> {
+        let mut annual = BTreeMap::new();
+
+        for (date, dividend) in self.dividends() {
+            *annual.entry(date.year()).or_insert(0.0) += dividend;
+        }
+
+        annual.into_iter().collect()
+    }
 }
 
 impl Statistics for Returns {
-    fn values(&self) -> Vec<f64> {
-        self.0.clone()
+    fn values(&self) -> &[f64] {
+        &self.0
     }
 }
@@ -1,12 +1,43 @@
+use crate::arima::{Arima, ArimaOrder, InformationCriterion};
 use crate::bond::{self, Bond, Frequency};
-use crate::methods::{internal_rate_of_return as irr, net_present_value};
+use crate::combinatorics;
+use crate::covariance::{self, Alignment, CovarianceMatrix, CovarianceMethod};
+use crate::dcf::{self, CostOfCapital, DiscountedCashFlow, TerminalValue};
+use crate::ddm::{self, DividendDiscountModel, GrowthStage};
+use crate::distributions::{
+    self, Bernoulli, Binomial, ChiSquared, ContinuousDistribution, DiscreteDistribution,
+    Exponential, Geometric, LogNormal, Normal, Poisson, StudentT, Uniform,
+};
+use crate::drawdown::{self, Drawdowns};
+use crate::empirical::{Bandwidth, BinRule, EmpiricalStatistics, MAX_BINS};
+use crate::garch::{Garch, Innovation, VarianceModel};
+use crate::hypothesis::{self, Alternative, HypothesisTests};
+use crate::inference::{self, Bootstrap, ConfidenceIntervals};
+use crate::inflation::{self, CpiSeries};
+use crate::loan::{self, Loan, LoanType, RateReset};
+use crate::matrix;
+use crate::methods::{
+    self, internal_rate_of_return as irr, net_present_value, PaymentTiming, TimeValueOfMoney,
+};
+use crate::performance::PerformanceStatistics;
 use crate::portfolio::Portfolio;
-use crate::stats::Statistics;
-use crate::stock_data::StockData;
-
-use chrono::{Months, Utc};
+use crate::quantile::{self, QuantileMethod, QuantileStatistics};
+use crate::realized_volatility::{self, VolatilityEstimator};
+use crate::regression::{self, LinearRegression};
+use crate::rolling::{RollingStatistics, Window, RISKMETRICS_LAMBDA};
+use crate::scenario::{self, Outcome, Scenario, ScenarioTree};
+use crate::stats::{
+    RunningCovariance, RunningStats, Statistics, StatsError, TRADING_DAYS_PER_YEAR,
+};
+use crate::stock_data::{Returns, StockData};
+use crate::time_series::{self, Deterministic, LagSelection, TimeSeriesStatistics};
+use crate::value_at_risk::{self, ValueAtRisk, VarMethod};
+
+use chrono::{DateTime, Months, Utc};
+use rand::{rngs::StdRng, SeedableRng};
 
 const PORTFOLIO_PATH: &str = "data/portfolio.json";
+const CPI_PATH: &str = "data/CPI.json";
 
 #[test]
 pub fn test_bond_issuance() -> Result<(), bond::Error> {
@@ -154,6 +185,7 @@ async fn test_stock_data_download() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 #[tokio::test]
+#[allow(unused_variables)]
 async fn test_portfolio_assets() -> Result<(), Box<dyn std::error::Error>> {
     let tickers = vec![
         "AAPL".to_string(),
@@ -187,6 +219,7 @@ async fn test_portfolio_assets() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 #[tokio::test]
+#[allow(unused_mut)]
 async fn test_portfolio_load() -> Result<(), Box<dyn std::error::Error>> {
     let mut portfolio = Portfolio::load(PORTFOLIO_PATH.into())?;
 
@@ -194,3 +227,2301 @@ async fn test_portfolio_load() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+pub fn test_annuities() -> Result<(), methods::Error> {
+    let ordinary = methods::present_value_annuity(100.0, 0.05, 10.0, PaymentTiming::End);
+    let due = methods::present_value_annuity(100.0, 0.05, 10.0, PaymentTiming::Beginning);
+
+    assert!((ordinary - 772.173_493).abs() < 1e-6);
+    assert!((due - ordinary * 1.05).abs() < 1e-9);
+
+    let future_value = methods::future_value_annuity(100.0, 0.05, 10.0, PaymentTiming::End);
+    assert!((future_value - 1_257.789_254).abs() < 1e-6);
+
+    let perpetuity = methods::present_value_perpetuity(100.0, 0.05, PaymentTiming::End)?;
+    assert!((perpetuity - 2_000.0).abs() < 1e-9);
+
+    let growing = methods::present_value_growing_perpetuity(100.0, 0.08, 0.03, PaymentTiming::End)?;
+    assert!((growing - 2_000.0).abs() < 1e-9);
+
+    assert!(
+        methods::present_value_growing_perpetuity(100.0, 0.03, 0.05, PaymentTiming::End).is_err()
+    );
+
+    // A growing annuity with zero growth is an ordinary annuity.
+    let growing_annuity =
+        methods::present_value_growing_annuity(100.0, 0.05, 0.0, 10.0, PaymentTiming::End);
+    assert!((growing_annuity - ordinary).abs() < 1e-9);
+
+    let growing_annuity =
+        methods::present_value_growing_annuity(100.0, 0.05, 0.05, 10.0, PaymentTiming::End);
+    assert!((growing_annuity - 1_000.0 / 1.05).abs() < 1e-9);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_time_value_of_money() -> Result<(), methods::Error> {
+    // 30 year mortgage of 200,000 at 6% compounded monthly.
+    let mortgage = TimeValueOfMoney {
+        periods: 360.0,
+        rate: 0.005,
+        present_value: 200_000.0,
+        ..Default::default()
+    };
+
+    let payment = mortgage.solve_payment()?;
+    assert!((payment + 1_199.101_050).abs() < 1e-5);
+
+    let tvm = TimeValueOfMoney {
+        payment,
+        ..mortgage.clone()
+    };
+    assert!((tvm.solve_rate()? - 0.005).abs() < 1e-9);
+    assert!((tvm.solve_periods()? - 360.0).abs() < 1e-6);
+    assert!((tvm.solve_present_value() - 200_000.0).abs() < 1e-4);
+    assert!(tvm.solve_future_value().abs() < 1e-4);
+
+    // Doubling money over 10 years.
+    let doubling = TimeValueOfMoney {
+        periods: 10.0,
+        present_value: -1_000.0,
+        future_value: 2_000.0,
+        ..Default::default()
+    };
+
+    assert!((doubling.solve_rate()? - 0.071_773_462).abs() < 1e-8);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_loan_amortization() -> Result<(), loan::Error> {
+    let mortgage = Loan::originate(
+        200_000.0,
+        0.06,
+        Frequency::Monthly,
+        360,
+        LoanType::FixedRate,
+    )?;
+
+    let schedule = mortgage.schedule()?;
+
+    assert_eq!(schedule.payments.len(), 360);
+    assert!((mortgage.payment() - 1_199.101_050).abs() < 1e-5);
+    assert!((schedule.total_interest() - 231_676.378).abs() < 1e-2);
+    assert!((schedule.total_principal() - 200_000.0).abs() < 1e-6);
+    assert!(schedule.payments.last().unwrap().balance.abs() < 1e-6);
+
+    // Without fees the APR equals the contract rate.
+    assert!((mortgage.annual_percentage_rate()? - 0.06).abs() < 1e-8);
+    assert!((mortgage.effective_apr()? - (1.005_f64.powf(12.0) - 1.0)).abs() < 1e-8);
+
+    // Fees raise the APR above the contract rate.
+    let with_fees = mortgage.clone().with_fees(4_000.0);
+    assert!(with_fees.annual_percentage_rate()? > 0.06);
+
+    // Prepayments shorten the term and reduce total interest.
+    let prepaid = mortgage.clone().with_recurring_prepayment(200.0);
+    let prepaid_schedule = prepaid.schedule()?;
+    assert!(prepaid_schedule.payments.len() < 360);
+    assert!(prepaid_schedule.total_interest() < schedule.total_interest());
+    assert!((prepaid_schedule.total_principal() - 200_000.0).abs() < 1e-6);
+
+    // Negative or non-finite prepayments would increase the balance, so they are rejected.
+    assert!(matches!(
+        mortgage.clone().with_prepayment(12, -500.0).schedule(),
+        Err(loan::Error::InvalidPrepayment(_))
+    ));
+    assert!(mortgage
+        .clone()
+        .with_recurring_prepayment(f64::NAN)
+        .annual_percentage_rate()
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_loan_structures() -> Result<(), loan::Error> {
+    let interest_only = Loan::originate(
+        100_000.0,
+        0.05,
+        Frequency::Annual,
+        5,
+        LoanType::InterestOnly,
+    )?;
+    let schedule = interest_only.schedule()?;
+
+    assert!(schedule.payments[..4].iter().all(|p| p.payment == 5_000.0));
+    assert_eq!(schedule.payments[4].principal, 100_000.0);
+    assert!((schedule.total_interest() - 25_000.0).abs() < 1e-9);
+
+    let balloon = Loan::originate(
+        100_000.0,
+        0.06,
+        Frequency::Monthly,
+        60,
+        LoanType::Balloon {
+            amortization_periods: 360,
+        },
+    )?;
+    let schedule = balloon.schedule()?;
+    let balloon_payment = schedule.payments.last().unwrap();
+
+    assert!(balloon_payment.principal > 90_000.0);
+    assert!(balloon_payment.balance.abs() < 1e-9);
+
+    assert!(Loan::originate(
+        100_000.0,
+        0.06,
+        Frequency::Monthly,
+        60,
+        LoanType::Balloon {
+            amortization_periods: 12
+        }
+    )
+    .is_err());
+
+    let adjustable = Loan::originate(
+        100_000.0,
+        0.04,
+        Frequency::Annual,
+        10,
+        LoanType::AdjustableRate {
+            rate_resets: vec![RateReset {
+                period: 6,
+                annual_interest_rate: 0.07,
+            }],
+        },
+    )?;
+    let schedule = adjustable.schedule()?;
+
+    assert_eq!(schedule.payments[5].rate, 0.07);
+    assert!(schedule.payments[5].payment > schedule.payments[4].payment);
+    assert!((schedule.total_principal() - 100_000.0).abs() < 1e-6);
+    assert!(schedule.payments.last().unwrap().balance.abs() < 1e-6);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_discounted_cash_flow() -> Result<(), dcf::Error> {
+    let cost_of_equity = dcf::capm_cost_of_equity(0.04, 1.2, 0.09);
+    assert!((cost_of_equity - 0.10).abs() < 1e-12);
+
+    let cost_of_capital = CostOfCapital {
+        cost_of_equity,
+        cost_of_debt: 0.05,
+        tax_rate: 0.25,
+        equity_value: 750.0,
+        debt_value: 250.0,
+    };
+
+    let wacc = cost_of_capital.wacc()?;
+    assert!((wacc - 0.084_375).abs() < 1e-12);
+
+    let model = DiscountedCashFlow {
+        free_cash_flows: vec![100.0, 110.0, 120.0, 130.0, 140.0],
+        discount_rate: 0.10,
+        terminal_value: TerminalValue::GordonGrowth { growth_rate: 0.02 },
+        net_debt: 200.0,
+        shares_outstanding: 10.0,
+    };
+
+    let valuation = model.valuation()?;
+
+    let terminal_value = 140.0 * 1.02 / (0.10 - 0.02);
+    assert!((valuation.terminal_value - terminal_value).abs() < 1e-9);
+
+    let present_value = net_present_value(0.0, model.free_cash_flows.clone(), 0.10)
+        + terminal_value / 1.1_f64.powi(5);
+    assert!((valuation.enterprise_value - present_value).abs() < 1e-9);
+    assert!((valuation.equity_value - (present_value - 200.0)).abs() < 1e-9);
+    assert!((valuation.per_share_value - (present_value - 200.0) / 10.0).abs() < 1e-9);
+
+    let exit_multiple = TerminalValue::ExitMultiple {
+        multiple: 10.0,
+        terminal_metric: 150.0,
+    };
+    let valuation = model.valuation_with(0.10, &exit_multiple)?;
+    assert_eq!(valuation.terminal_value, 1_500.0);
+
+    let table = model.sensitivity(&[0.08, 0.10, 0.12], &[0.01, 0.02, 0.10])?;
+
+    assert_eq!(table.per_share_values.len(), 3);
+    assert!(table.per_share_values[0][2].is_none());
+    assert!(matches!(
+        model.valuation_with(0.02, &TerminalValue::GordonGrowth { growth_rate: 0.02 }),
+        Err(dcf::Error::NonConvergentTerminalValue)
+    ));
+
+    // Value rises as the discount rate falls and as terminal growth rises.
+    let low_rate = table.per_share_values[0][1].unwrap();
+    let base = table.per_share_values[1][1].unwrap();
+    let low_growth = table.per_share_values[1][0].unwrap();
+    assert!(low_rate > base && base > low_growth);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_dividend_discount_models() -> Result<(), ddm::Error> {
+    let gordon = DividendDiscountModel::GordonGrowth {
+        next_dividend: 2.0,
+        growth_rate: 0.05,
+    };
+
+    assert!((gordon.value(0.10)? - 40.0).abs() < 1e-9);
+    assert!((gordon.implied_required_return(40.0)? - 0.10).abs() < 1e-12);
+    assert!(matches!(
+        gordon.value(0.04),
+        Err(ddm::Error::NonConvergentGrowth)
+    ));
+    assert!(matches!(
+        gordon.implied_required_return(0.0),
+        Err(ddm::Error::InvalidPrice(_))
+    ));
+    assert!(gordon.implied_required_return(f64::NAN).is_err());
+
+    // Two years of 10% growth, then 5% forever.
+    let multi_stage = DividendDiscountModel::MultiStage {
+        current_dividend: 2.0,
+        stages: vec![GrowthStage {
+            periods: 2,
+            growth_rate: 0.10,
+        }],
+        terminal_growth_rate: 0.05,
+    };
+
+    let d1 = 2.2;
+    let d2 = 2.42;
+    let terminal = d2 * 1.05 / 0.05;
+    let expected = d1 / 1.1 + (d2 + terminal) / 1.1_f64.powi(2);
+
+    let value = multi_stage.value(0.10)?;
+    assert!((value - expected).abs() < 1e-9);
+    assert!((multi_stage.implied_required_return(value)? - 0.10).abs() < 1e-9);
+
+    let h_model = DividendDiscountModel::HModel {
+        current_dividend: 2.0,
+        short_term_growth_rate: 0.15,
+        long_term_growth_rate: 0.05,
+        half_life: 5.0,
+    };
+
+    // 2(1.05)/0.05 + 2(5)(0.10)/0.05
+    let value = h_model.value(0.10)?;
+    assert!((value - 62.0).abs() < 1e-9);
+    assert!((h_model.implied_required_return(value)? - 0.10).abs() < 1e-9);
+    assert!(matches!(
+        h_model.implied_required_return(-10.0),
+        Err(ddm::Error::InvalidPrice(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_dividend_growth() -> Result<(), Box<dyn std::error::Error>> {
+    let portfolio = Portfolio::load(PORTFOLIO_PATH.into())?;
+
+    let msft = &portfolio.assets["MSFT"].stock_data;
+
+    assert_eq!(msft.dividends().len(), 4);
+    assert_eq!(msft.annual_dividends(), vec![(2022, 0.68), (2023, 2.04)]);
+
+    // A flat quarterly dividend has no growth, although the partial years at either end of the
+    // data have different totals.
+    let growth = ddm::compound_dividend_growth(msft)?;
+    assert!(growth.abs() < 1e-9);
+
+    let growth = ddm::average_dividend_growth(msft)?;
+    assert!(growth.abs() < 1e-9);
+
+    // Five quarterly payments compare the first and last trailing-year totals, and the last
+    // payment with the first.
+    let aapl = &portfolio.assets["AAPL"].stock_data;
+    let growth = ddm::compound_dividend_growth(aapl)?;
+    assert!((growth - ((0.88f64 / 0.87).powi(4) - 1.0)).abs() < 1e-9);
+
+    let growth = ddm::average_dividend_growth(aapl)?;
+    assert!((growth - (0.24 / 0.23 - 1.0)).abs() < 1e-9);
+
+    let meta = &portfolio.assets["META"].stock_data;
+    assert!(ddm::compound_dividend_growth(meta).is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_inflation_adjustment() -> Result<(), Box<dyn std::error::Error>> {
+    let real = inflation::real_rate(0.08, 0.03);
+    assert!((real - 0.048_543_689).abs() < 1e-9);
+    assert!((inflation::nominal_rate(real, 0.03) - 0.08).abs() < 1e-12);
+
+    let cpi = CpiSeries::from_file(CPI_PATH.into())?;
+    assert_eq!(cpi.inflation_rates().len(), cpi.0.len() - 1);
+
+    let portfolio = Portfolio::load(PORTFOLIO_PATH.into())?;
+    let nominal_returns = portfolio.asset_returns();
+    let real_returns = portfolio.real_asset_returns(&cpi)?;
+
+    let aapl = &portfolio.assets["AAPL"].stock_data;
+    let nominal = &nominal_returns["AAPL"];
+    let real = &real_returns["AAPL"];
+
+    assert_eq!(nominal.count(), real.count());
+
+    // Compounded real growth equals nominal growth deflated by cumulative inflation.
+    let growth = |r: &Returns| r.0.iter().map(|v| 1.0 + v).product::<f64>();
+    let first = aapl.data.first().unwrap().date;
+    let last = aapl.data.last().unwrap().date;
+    let cumulative_inflation = cpi.inflation_between(first, last)?;
+
+    assert!((growth(real) - growth(nominal) / (1.0 + cumulative_inflation)).abs() < 1e-9);
+    assert!(real.geometric_mean() < nominal.geometric_mean());
+
+    let real_prices = cpi.real_prices(aapl, last)?;
+    assert_eq!(
+        *real_prices.last().unwrap(),
+        aapl.data.last().unwrap().close
+    );
+
+    let early = chrono::DateTime::parse_from_rfc3339("2000-01-01T00:00:00Z")?.with_timezone(&Utc);
+    assert!(cpi.value_at(early).is_err());
+
+    // Monthly inflation is spread evenly over the days of the month.
+    let date = |s: &str| -> Result<DateTime<Utc>, chrono::ParseError> {
+        Ok(chrono::DateTime::parse_from_rfc3339(s)?.with_timezone(&Utc))
+    };
+    let january =
+        cpi.inflation_between(date("2023-01-01T00:00:00Z")?, date("2023-02-01T00:00:00Z")?)?;
+    let one_day =
+        cpi.inflation_between(date("2023-01-15T00:00:00Z")?, date("2023-01-16T00:00:00Z")?)?;
+    let first_day =
+        cpi.inflation_between(date("2023-01-31T00:00:00Z")?, date("2023-02-01T00:00:00Z")?)?;
+    assert!((one_day - ((1.0 + january).powf(1.0 / 31.0) - 1.0)).abs() < 1e-12);
+    assert!((first_day - one_day).abs() < 1e-12);
+
+    // Real portfolio returns deflate the value-weighted nominal returns.
+    let mut portfolio = portfolio.clone();
+    portfolio.assets.get_mut("AAPL").unwrap().amount_invested = 6_000.0;
+    portfolio.assets.get_mut("MSFT").unwrap().amount_invested = 4_000.0;
+
+    let nominal = portfolio.returns();
+    let real = portfolio.real_returns(&cpi)?;
+    assert_eq!(nominal.count(), aapl.data.len() - 1);
+    assert_eq!(real.count(), nominal.count());
+
+    for (i, (n, r)) in nominal.0.iter().zip(&real.0).enumerate() {
+        let inflation = cpi.inflation_between(aapl.data[i].date, aapl.data[i + 1].date)?;
+        assert!((r - inflation::real_rate(*n, inflation)).abs() < 1e-12);
+    }
+
+    assert!(real.try_population_mean()? < nominal.try_population_mean()?);
+    assert!(real.try_sharpe_ratio(0.0)? < nominal.try_sharpe_ratio(0.0)?);
+    assert!(Portfolio::default()
+        .returns()
+        .try_population_mean()
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_checked_statistics() {
+    let empty = Returns(vec![]);
+
+    assert_eq!(empty.try_population_mean(), Err(StatsError::EmptyInput));
+    assert_eq!(empty.try_sample_mean(), Err(StatsError::EmptyInput));
+    assert_eq!(empty.try_max(), Err(StatsError::EmptyInput));
+    assert!(empty.sample_mean().is_nan());
+
+    let negative = Returns(vec![-0.03, -0.01, -0.02, -0.05]);
+
+    assert_eq!(negative.max(), -0.01);
+    assert_eq!(negative.min(), -0.05);
+    assert!((negative.range() - 0.04).abs() < 1e-12);
+
+    assert_eq!(
+        negative.try_weighted_average(vec![0.5, 0.5]),
+        Err(StatsError::LengthMismatch {
+            expected: 4,
+            actual: 2
+        })
+    );
+    assert_eq!(
+        negative.try_weighted_average(vec![0.5, 0.5, 0.5, 0.5]),
+        Err(StatsError::InvalidWeights)
+    );
+    assert!((negative.weighted_average(vec![0.1, 0.2, 0.3, 0.4]) + 0.031).abs() < 1e-12);
+
+    let values = Returns((1..=9).map(f64::from).collect());
+
+    assert_eq!(values.try_percentile(50.0), Ok(5.0));
+    assert_eq!(values.try_percentile(25.0), Ok(2.5));
+    assert_eq!(values.try_percentile(0.0), Ok(1.0));
+    assert_eq!(values.try_percentile(100.0), Ok(9.0));
+    assert_eq!(
+        values.try_percentile(101.0),
+        Err(StatsError::InvalidPercentile(101.0))
+    );
+    assert!((values.try_harmonic_mean().unwrap() - 3.181_371_861).abs() < 1e-9);
+
+    let constant = Returns(vec![0.01; 5]);
+
+    assert_eq!(constant.try_sample_variance(), Ok(0.0));
+    assert_eq!(
+        constant.try_sharpe_ratio(0.0),
+        Err(StatsError::DegenerateVariance)
+    );
+    assert_eq!(
+        Returns(vec![0.01]).try_sample_variance(),
+        Err(StatsError::InsufficientData {
+            required: 2,
+            actual: 1
+        })
+    );
+    assert_eq!(
+        Returns(vec![0.01, f64::NAN]).try_population_mean(),
+        Err(StatsError::NotANumber)
+    );
+
+    let skewed = Returns(vec![0.0, 0.0, 0.0, 0.0, 1.0]);
+    assert!(skewed.skewness() > 0.0);
+
+    let z_scores = values.z_scores();
+    assert!((z_scores.iter().sum::<f64>()).abs() < 1e-12);
+}
+
+#[test]
+pub fn test_single_pass_statistics() -> Result<(), Box<dyn std::error::Error>> {
+    let portfolio = Portfolio::load(PORTFOLIO_PATH.into())?;
+    let returns = portfolio.assets["AAPL"].stock_data.returns();
+    let values = &returns.0;
+
+    // Two-pass reference calculations.
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let m2 = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>();
+    let m3 = values.iter().map(|v| (v - mean).powi(3)).sum::<f64>();
+    let m4 = values.iter().map(|v| (v - mean).powi(4)).sum::<f64>();
+    let std_dev = (m2 / (n - 1.0)).sqrt();
+
+    assert!((returns.population_mean() - mean).abs() < 1e-15);
+    assert!((returns.sample_variance() - m2 / (n - 1.0)).abs() < 1e-15);
+    assert!((returns.skewness() - m3 / n / std_dev.powi(3)).abs() < 1e-10);
+    assert!((returns.excess_kurtosis() - (m4 / n / std_dev.powi(4) - 3.0)).abs() < 1e-10);
+
+    // Slices and vectors implement the trait directly.
+    assert_eq!(
+        values.as_slice().population_mean(),
+        returns.population_mean()
+    );
+    assert_eq!(values.max(), returns.max());
+
+    let other = portfolio.assets["MSFT"].stock_data.returns();
+    let covariance = values
+        .iter()
+        .zip(other.0.iter())
+        .map(|(a, b)| (a - mean) * (b - other.population_mean()))
+        .sum::<f64>()
+        / (n - 1.0);
+    assert!((returns.covariance(&other) - covariance).abs() < 1e-15);
+
+    // The sorted lookup matches a scan over every value.
+    let mantissa = 10_f64.powf(5.);
+    let expected_p = values
+        .iter()
+        .map(|r| {
+            let lower_bound = (r * mantissa).floor() / mantissa;
+            let upper_bound = (r * mantissa).ceil() / mantissa;
+            returns.probability_bounds(lower_bound, upper_bound) * r
+        })
+        .sum::<f64>();
+    assert!((returns.expected_probability() - expected_p).abs() < 1e-15);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_running_statistics() -> Result<(), Box<dyn std::error::Error>> {
+    let portfolio = Portfolio::load(PORTFOLIO_PATH.into())?;
+    let aapl = portfolio.assets["AAPL"].stock_data.returns();
+    let msft = portfolio.assets["MSFT"].stock_data.returns();
+
+    let mut running = RunningStats::new();
+    let mut running_covariance = RunningCovariance::new();
+
+    for (a, m) in aapl.0.iter().zip(msft.0.iter()) {
+        running.push(*a);
+        running_covariance.push(*a, *m);
+    }
+
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-12 * b.abs().max(1.0);
+
+    assert_eq!(running.count(), aapl.count());
+    assert!(close(running.mean(), aapl.population_mean()));
+    assert!(close(running.sample_variance(), aapl.sample_variance()));
+    assert!(close(running.skewness(), aapl.skewness()));
+    assert!(close(running.excess_kurtosis(), aapl.excess_kurtosis()));
+    assert_eq!(running.min(), aapl.min());
+    assert_eq!(running.max(), aapl.max());
+    assert!(close(
+        running_covariance.sample_covariance(),
+        aapl.covariance(&msft)
+    ));
+    assert!(close(
+        running_covariance.correlation(),
+        aapl.correlation(&msft)
+    ));
+
+    // Accumulate chunks on separate threads and merge the results.
+    let (merged, merged_covariance) = std::thread::scope(|scope| {
+        let handles = aapl
+            .0
+            .chunks(37)
+            .zip(msft.0.chunks(37))
+            .map(|(a, m)| {
+                scope.spawn(move || {
+                    (
+                        RunningStats::from_values(a),
+                        RunningCovariance::from_values(a, m),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut merged = RunningStats::new();
+        let mut merged_covariance = RunningCovariance::new();
+
+        for handle in handles {
+            let (stats, covariance) = handle.join().unwrap();
+            merged.merge(&stats);
+            merged_covariance.merge(&covariance);
+        }
+
+        (merged, merged_covariance)
+    });
+
+    assert_eq!(merged.count(), running.count());
+    assert!(close(merged.mean(), running.mean()));
+    assert!(close(merged.sample_variance(), running.sample_variance()));
+    assert!(close(merged.skewness(), running.skewness()));
+    assert!(close(merged.excess_kurtosis(), running.excess_kurtosis()));
+    assert_eq!(merged.min(), running.min());
+    assert_eq!(merged.max(), running.max());
+    assert!(close(
+        merged_covariance.sample_covariance(),
+        running_covariance.sample_covariance()
+    ));
+
+    assert!(RunningStats::new().mean().is_nan());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_rolling_statistics() -> Result<(), Box<dyn std::error::Error>> {
+    let portfolio = Portfolio::load(PORTFOLIO_PATH.into())?;
+    let aapl = portfolio.assets["AAPL"].stock_data.returns();
+    let msft = portfolio.assets["MSFT"].stock_data.returns();
+
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-10 * b.abs().max(1.0);
+
+    for size in [20, 60] {
+        let window = Window::new(size);
+
+        let means = aapl.rolling_mean(window);
+        let variances = aapl.rolling_variance(window);
+        let sharpe_ratios = aapl.rolling_sharpe_ratio(window, 0.0001);
+        let correlations = aapl.rolling_correlation(&msft, window);
+
+        assert_eq!(means.len(), aapl.count());
+        assert!(means[..size - 1].iter().all(|v| v.is_nan()));
+
+        for i in size - 1..aapl.count() {
+            let a = Returns(aapl.0[i + 1 - size..=i].to_vec());
+            let m = Returns(msft.0[i + 1 - size..=i].to_vec());
+
+            assert!(close(means[i], a.population_mean()));
+            assert!(close(variances[i], a.sample_variance()));
+            assert!(close(sharpe_ratios[i], a.sharpe_ratio(0.0001)));
+            assert!(close(correlations[i], a.correlation(&m)));
+        }
+    }
+
+    // Partial windows are produced once the minimum number of periods is reached.
+    let window = Window::new(252).with_min_periods(20);
+    let volatility = aapl.rolling_volatility(window, TRADING_DAYS_PER_YEAR);
+
+    assert!(volatility[18].is_nan());
+    assert!(close(
+        volatility[19],
+        aapl.0[..20].sample_std_dev() * TRADING_DAYS_PER_YEAR.sqrt()
+    ));
+    // The series is shorter than a full window, so the last value covers every return.
+    assert!(close(
+        volatility[aapl.count() - 1],
+        aapl.sample_std_dev() * TRADING_DAYS_PER_YEAR.sqrt()
+    ));
+
+    // Windows containing a NaN are NaN, and later windows recover once it leaves.
+    let gappy = vec![0.01, -0.02, f64::NAN, 0.03, 0.02, -0.01, 0.04];
+    let means = gappy.rolling_mean(Window::new(3));
+    let correlations = gappy.rolling_correlation(&aapl.0[..7].to_vec(), Window::new(3));
+
+    assert!(means[2..5].iter().all(|v| v.is_nan()));
+    assert!(close(means[5], gappy[3..6].population_mean()));
+    assert!(close(means[6], gappy[4..7].population_mean()));
+    assert!(correlations[4].is_nan());
+    assert!(close(
+        correlations[6],
+        gappy[4..7].correlation(&aapl.0[4..7])
+    ));
+
+    let values = vec![0.01, -0.02, 0.03];
+    let ewma_mean = values.ewma_mean(0.5, 1);
+    let ewma_variance = values.ewma_variance(RISKMETRICS_LAMBDA, 2);
+
+    assert_eq!(ewma_mean[..2], [0.01, -0.005]);
+    assert!(close(ewma_mean[2], 0.0125));
+    assert!(ewma_variance[0].is_nan());
+
+    let expected = 0.94 * 0.0001 + 0.06 * 0.0004;
+    assert!(close(ewma_variance[1], expected));
+    assert!(close(ewma_variance[2], 0.94 * expected + 0.06 * 0.0009));
+
+    // A NaN is skipped, and the average carries on from the values before it.
+    let gappy_mean = vec![0.01, f64::NAN, -0.02, 0.03].ewma_mean(0.5, 1);
+    let gappy_variance = vec![0.01, -0.02, f64::NAN, 0.03].ewma_variance(RISKMETRICS_LAMBDA, 2);
+
+    assert!(gappy_mean[1].is_nan());
+    assert_eq!(gappy_mean[2], ewma_mean[1]);
+    assert!(close(gappy_mean[3], ewma_mean[2]));
+    assert!(gappy_variance[2].is_nan());
+    assert!(close(gappy_variance[3], ewma_variance[2]));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_quantile_methods() -> Result<(), StatsError> {
+    use QuantileMethod::*;
+
+    let values = vec![20.0, 3.0, 6.0, 7.0, 8.0, 8.0, 10.0, 13.0, 15.0, 16.0];
+    let methods = [
+        Type1, Type2, Type3, Type4, Type5, Type6, Type7, Type8, Type9,
+    ];
+
+    // Reference values from R's `quantile(x, p, type = 1:9)`.
+    let lower_quartiles = [7.0, 7.0, 6.0, 6.5, 7.0, 6.75, 7.25, 6.916_666_667, 6.937_5];
+    let medians = [8.0, 9.0, 8.0, 8.0, 9.0, 9.0, 9.0, 9.0, 9.0];
+    let upper_quartiles = [
+        15.0,
+        15.0,
+        15.0,
+        14.0,
+        15.0,
+        15.25,
+        14.5,
+        15.083_333_333,
+        15.062_5,
+    ];
+
+    for (i, method) in methods.iter().enumerate() {
+        let [lower, median, upper] = values.quartiles(*method)?;
+
+        assert!((lower - lower_quartiles[i]).abs() < 1e-9, "{:?}", method);
+        assert!((median - medians[i]).abs() < 1e-9, "{:?}", method);
+        assert!((upper - upper_quartiles[i]).abs() < 1e-9, "{:?}", method);
+
+        // Every definition returns the extremes at p = 0 and p = 1.
+        assert_eq!(values.try_quantile(0.0, *method)?, 3.0);
+        assert_eq!(values.try_quantile(1.0, *method)?, 20.0);
+    }
+
+    assert_eq!(values.interquartile_range(Type7)?, 7.25);
+    assert_eq!(values.percentile(25.0), values.quantile(0.25, Type6));
+    assert!(values.try_quantile(1.5, Type7).is_err());
+    assert!(matches!(
+        quantile::quantile_sorted(&[], 0.5, Type7),
+        Err(StatsError::EmptyInput)
+    ));
+    assert!(quantile::quantile_sorted(&[1.0, 2.0], f64::NAN, Type7).is_err());
+
+    let deciles = (1..=10)
+        .map(f64::from)
+        .collect::<Vec<f64>>()
+        .deciles(Type7)?;
+    assert_eq!(deciles.len(), 9);
+    assert!((deciles[0] - 1.9).abs() < 1e-12);
+    assert!((deciles[8] - 9.1).abs() < 1e-12);
+
+    let summary = values.five_number_summary(Type7)?;
+    assert_eq!(summary.min, 3.0);
+    assert_eq!(summary.median, 9.0);
+    assert_eq!(summary.max, 20.0);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_empirical_distribution() -> Result<(), Box<dyn std::error::Error>> {
+    let portfolio = Portfolio::load(PORTFOLIO_PATH.into())?;
+    let returns = portfolio.assets["AAPL"].stock_data.returns();
+
+    let sturges = returns.histogram(BinRule::Sturges)?;
+    assert_eq!(sturges.counts.len(), 9);
+    assert_eq!(sturges.total(), returns.count());
+    assert_eq!(sturges.edges[0], returns.min());
+    assert!((sturges.edges[9] - returns.max()).abs() < 1e-12);
+
+    let density = sturges.densities().iter().sum::<f64>() * sturges.bin_width();
+    assert!((density - 1.0).abs() < 1e-12);
+
+    let freedman_diaconis = returns.histogram(BinRule::FreedmanDiaconis)?;
+    assert_eq!(freedman_diaconis.total(), returns.count());
+    assert!(freedman_diaconis.counts.len() > sturges.counts.len());
+
+    let fixed = vec![0.0, 0.5, 1.0, 1.5, 2.0].histogram(BinRule::FixedWidth(1.0))?;
+    assert_eq!(fixed.counts, vec![2, 2, 1]);
+    assert_eq!(fixed.centers(), vec![0.5, 1.5, 2.5]);
+
+    // Invalid widths, infinite values and excessive bin counts are rejected before binning.
+    let invalid = |values: Vec<f64>, rule: BinRule| {
+        matches!(values.histogram(rule), Err(StatsError::InvalidDomain))
+    };
+    assert!(invalid(vec![0.0, 1.0], BinRule::FixedWidth(f64::NAN)));
+    assert!(invalid(vec![0.0, 1.0], BinRule::FixedWidth(1e-12)));
+    assert!(invalid(vec![0.0, f64::INFINITY], BinRule::FixedWidth(1.0)));
+    assert!(invalid(vec![0.0, 1.0], BinRule::Count(MAX_BINS + 1)));
+    assert_eq!(
+        vec![0.0, 1.0]
+            .histogram(BinRule::Count(MAX_BINS))?
+            .counts
+            .len(),
+        MAX_BINS
+    );
+
+    let ecdf = vec![3.0, 1.0, 2.0, 2.0].ecdf()?;
+    assert_eq!(ecdf.cdf(0.5), 0.0);
+    assert_eq!(ecdf.cdf(2.0), 0.75);
+    assert_eq!(ecdf.cdf(3.0), 1.0);
+    assert_eq!(ecdf.inverse(0.5)?, 2.0);
+    assert_eq!(ecdf.inverse(0.75)?, 2.0);
+    assert_eq!(ecdf.inverse(0.76)?, 3.0);
+
+    let kde = returns.kernel_density(Bandwidth::Silverman)?;
+    let grid = kde.evaluate(2_001);
+    let step = grid[1].0 - grid[0].0;
+    let integral = grid.iter().map(|(_, d)| d).sum::<f64>() * step;
+    assert!((integral - 1.0).abs() < 1e-3);
+
+    let scott = returns.kernel_density(Bandwidth::Scott)?;
+    let expected = 1.06 * returns.sample_std_dev() * (returns.count() as f64).powf(-0.2);
+    assert!((scott.bandwidth - expected).abs() < 1e-15);
+    assert!(kde.bandwidth <= scott.bandwidth);
+
+    assert!(vec![1.0; 10].kernel_density(Bandwidth::Silverman).is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_continuous_distributions() -> Result<(), distributions::Error> {
+    let close = |a: f64, b: f64, tolerance: f64| (a - b).abs() < tolerance;
+
+    let normal = Normal::new(0.0, 1.0)?;
+    assert!(close(normal.cdf(1.96), 0.975_002_104_851_779_5, 1e-14));
+    assert!(close(normal.cdf(-5.0), 2.866_515_718_791_939e-7, 1e-20));
+    assert!(close(
+        normal.inverse_cdf(0.975),
+        1.959_963_984_540_054,
+        1e-12
+    ));
+    assert!(close(
+        normal.inverse_cdf(1e-10),
+        -6.361_340_902_404_056,
+        1e-9
+    ));
+    assert!(normal.try_inverse_cdf(1.5).is_err());
+
+    let lognormal = LogNormal::new(0.0, 0.5)?;
+    assert!(close(lognormal.cdf(1.0), 0.5, 1e-15));
+    assert!(close(lognormal.mean(), 0.125_f64.exp(), 1e-15));
+    assert!(close(lognormal.inverse_cdf(lognormal.cdf(1.7)), 1.7, 1e-12));
+
+    // Critical values from standard t and chi-square tables.
+    let t = StudentT::standard(10.0)?;
+    assert!(close(t.inverse_cdf(0.975), 2.228_138_851_986_274, 1e-9));
+    assert!(close(t.cdf(2.228_138_851_986_274), 0.975, 1e-12));
+    assert!(close(t.cdf(0.0), 0.5, 1e-15));
+    assert!(close(t.variance(), 1.25, 1e-15));
+
+    let chi_squared = ChiSquared::new(3.0)?;
+    assert!(close(chi_squared.cdf(7.814_727_903_251_178), 0.95, 1e-12));
+    assert!(close(
+        chi_squared.inverse_cdf(0.95),
+        7.814_727_903_251_178,
+        1e-9
+    ));
+
+    let uniform = Uniform::new(-1.0, 3.0)?;
+    assert_eq!(uniform.cdf(1.0), 0.5);
+    assert_eq!(uniform.inverse_cdf(0.25), 0.0);
+    assert!(close(uniform.variance(), 16.0 / 12.0, 1e-15));
+
+    let exponential = Exponential::new(2.0)?;
+    assert!(close(exponential.cdf(0.5), 1.0 - (-1.0_f64).exp(), 1e-15));
+    assert!(close(
+        exponential.inverse_cdf(exponential.cdf(0.3)),
+        0.3,
+        1e-12
+    ));
+
+    // Densities integrate to their cumulative distribution functions.
+    let step = 1e-3;
+    let integral = (0..2_000)
+        .map(|i| t.pdf(-1.0 + (i as f64 + 0.5) * step) * step)
+        .sum::<f64>();
+    assert!(close(integral, t.cdf(1.0) - t.cdf(-1.0), 1e-6));
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let samples = Normal::new(0.001, 0.02)?.sample_n(&mut rng, 20_000);
+
+    let fitted = Normal::fit(&samples)?;
+    assert!(close(fitted.mean, 0.001, 1e-3));
+    assert!(close(fitted.std_dev, 0.02, 1e-3));
+
+    let gross = samples.iter().map(|r| 1.0 + r).collect::<Vec<f64>>();
+    assert!(close(LogNormal::fit(&gross)?.sigma, 0.02, 1e-3));
+
+    let samples = StudentT::new(0.0005, 0.01, 4.0)?.sample_n(&mut rng, 20_000);
+    let fitted = StudentT::fit(&samples)?;
+    assert!(close(fitted.degrees_of_freedom, 4.0, 0.5));
+    assert!(close(fitted.scale, 0.01, 5e-4));
+
+    let samples = ChiSquared::new(5.0)?.sample_n(&mut rng, 20_000);
+    assert!(close(
+        ChiSquared::fit(&samples)?.degrees_of_freedom,
+        5.0,
+        0.2
+    ));
+
+    let samples = Exponential::new(2.0)?.sample_n(&mut rng, 20_000);
+    assert!(close(Exponential::fit(&samples)?.rate, 2.0, 0.1));
+    assert!(matches!(
+        Exponential::fit(&[0.0, 0.0, 0.0][..]),
+        Err(distributions::Error::InvalidData)
+    ));
+
+    let samples = Uniform::new(-1.0, 3.0)?.sample_n(&mut rng, 20_000);
+    let fitted = Uniform::fit(&samples)?;
+    assert!(fitted.lower >= -1.0 && fitted.upper <= 3.0);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_fitted_tails() -> Result<(), Box<dyn std::error::Error>> {
+    let portfolio = Portfolio::load(PORTFOLIO_PATH.into())?;
+    let returns = portfolio.assets["AAPL"].stock_data.returns();
+
+    let normal = Normal::fit(&returns)?;
+    let t = StudentT::fit(&returns)?;
+
+    assert!(t.log_likelihood(&returns.0) >= normal.log_likelihood(&returns.0));
+
+    let tails = distributions::compare_quantiles(&normal, &returns, &[0.01, 0.05, 0.95, 0.99])?;
+
+    assert_eq!(tails.len(), 4);
+    assert!(tails
+        .iter()
+        .all(|(_, empirical, fitted)| empirical.is_finite() && fitted.is_finite()));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_combinatorics() {
+    assert_eq!(combinatorics::factorial(0), Some(1));
+    assert_eq!(
+        combinatorics::factorial(20),
+        Some(2_432_902_008_176_640_000)
+    );
+    assert_eq!(combinatorics::factorial(35), None);
+
+    assert_eq!(combinatorics::combinations(5, 2), Some(10));
+    assert_eq!(combinatorics::combinations(52, 5), Some(2_598_960));
+    assert_eq!(combinatorics::combinations(3, 5), Some(0));
+    assert_eq!(
+        combinatorics::combinations(100, 50),
+        Some(100_891_344_545_564_193_334_812_497_256)
+    );
+    assert_eq!(combinatorics::combinations(1_000, 500), None);
+
+    assert_eq!(combinatorics::permutations(5, 2), Some(20));
+    assert_eq!(combinatorics::permutations(10, 10), Some(3_628_800));
+
+    let ln = combinatorics::ln_combinations(100, 50);
+    assert!((ln - 100_891_344_545_564_193_334_812_497_256_f64.ln()).abs() < 1e-10);
+    assert!(combinatorics::ln_combinations(1_000, 500).is_finite());
+    assert!((combinatorics::ln_permutations(5, 2) - 20_f64.ln()).abs() < 1e-12);
+}
+
+#[test]
+pub fn test_discrete_distributions() -> Result<(), distributions::Error> {
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+
+    let binomial = Binomial::new(10, 0.3)?;
+    assert!(close(binomial.pmf(3), 0.266_827_932));
+    assert!(close(
+        binomial.cdf(3),
+        (0..=3).map(|k| binomial.pmf(k)).sum::<f64>()
+    ));
+    assert!(close((0..=10).map(|k| binomial.pmf(k)).sum::<f64>(), 1.0));
+    assert_eq!(binomial.quantile(0.5)?, 3);
+    assert_eq!(binomial.quantile(1.0)?, 10);
+    assert!(close(binomial.variance(), 2.1));
+
+    let bernoulli = Bernoulli::new(0.25)?;
+    assert_eq!(bernoulli.pmf(1), 0.25);
+    assert_eq!(bernoulli.quantile(0.75)?, 0);
+    assert_eq!(bernoulli.quantile(0.76)?, 1);
+
+    let poisson = Poisson::new(4.0)?;
+    assert!(close(poisson.pmf(2), 8.0 * (-4.0_f64).exp()));
+    assert!(close(
+        poisson.cdf(6),
+        (0..=6).map(|k| poisson.pmf(k)).sum::<f64>()
+    ));
+    assert_eq!(poisson.quantile(0.5)?, 4);
+
+    // With no events expected, the distribution is degenerate at zero.
+    let degenerate = Poisson::new(0.0)?;
+    assert_eq!(degenerate.pmf(0), 1.0);
+    assert_eq!(degenerate.pmf(1), 0.0);
+    assert_eq!(degenerate.cdf(0), 1.0);
+    assert_eq!(degenerate.quantile(0.99)?, 0);
+    assert!(Poisson::new(-1.0).is_err());
+
+    let geometric = Geometric::new(0.2)?;
+    assert_eq!(geometric.pmf(0), 0.0);
+    assert!(close(geometric.pmf(3), 0.8 * 0.8 * 0.2));
+    assert!(close(geometric.cdf(3), 1.0 - 0.8_f64.powi(3)));
+    assert_eq!(geometric.quantile(0.5)?, 4);
+    assert_eq!(geometric.quantile(0.0)?, 1);
+    assert_eq!(poisson.quantile(0.0)?, 0);
+    assert!(Geometric::new(0.0).is_err());
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let samples = binomial.sample_n(&mut rng, 10_000);
+    let mean = samples.iter().sum::<u64>() as f64 / samples.len() as f64;
+    assert!((mean - 3.0).abs() < 0.1);
+
+    let samples = poisson.sample_n(&mut rng, 10_000);
+    let mean = samples.iter().sum::<u64>() as f64 / samples.len() as f64;
+    assert!((mean - 4.0).abs() < 0.1);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_scenario_analysis() -> Result<(), scenario::Error> {
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+
+    // Earnings per share conditional on the interest rate environment.
+    let tree = ScenarioTree::new(vec![
+        Scenario::new(
+            "declining rates",
+            0.6,
+            vec![
+                Outcome::new("beat", 0.6, 2.60),
+                Outcome::new("miss", 0.4, 2.45),
+            ],
+        )?,
+        Scenario::new(
+            "stable rates",
+            0.4,
+            vec![
+                Outcome::new("beat", 0.2, 2.20),
+                Outcome::new("miss", 0.8, 2.00),
+            ],
+        )?,
+    ])?;
+
+    assert!(close(
+        tree.conditional_expected_value("declining rates")?,
+        2.54
+    ));
+    assert!(close(
+        tree.conditional_expected_value("stable rates")?,
+        2.04
+    ));
+    assert!(close(tree.expected_value(), 0.6 * 2.54 + 0.4 * 2.04));
+    assert!(close(
+        tree.expected_value(),
+        tree.joint_outcomes().expected_value()
+    ));
+    assert!(close(tree.variance(), tree.joint_outcomes().variance()));
+
+    let p_beat = tree.total_probability("beat")?;
+    assert!(close(p_beat, 0.6 * 0.6 + 0.4 * 0.2));
+
+    let posterior = tree.posterior("beat")?;
+    assert!(close(posterior[0].1, 0.36 / 0.44));
+    assert!(close(posterior.iter().map(|(_, p)| p).sum::<f64>(), 1.0));
+
+    let updated = tree.update("beat")?;
+    assert!(close(updated.scenarios[0].probability, 0.36 / 0.44));
+    assert!(updated.expected_value() > tree.expected_value());
+
+    assert!(close(scenario::bayes(0.6, 0.6, p_beat)?, 0.36 / 0.44));
+    assert!(tree.total_probability("inline").is_err());
+    assert!(tree.conditional_expected_value("rising rates").is_err());
+    assert!(ScenarioTree::new(vec![Scenario::new(
+        "only",
+        0.5,
+        vec![Outcome::new("x", 1.0, 1.0)]
+    )?])
+    .is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_hypothesis_tests() -> Result<(), hypothesis::Error> {
+    let close = |a: f64, b: f64, tolerance: f64| (a - b).abs() < tolerance;
+
+    // Reference values from R's t.test, including the `sleep` dataset.
+    let one_sample = [1.0, 2.0, 3.0, 4.0, 5.0].t_test(0.0, Alternative::TwoSided)?;
+    assert!(close(one_sample.statistic, 4.2426, 1e-4));
+    assert_eq!(one_sample.degrees_of_freedom, Some(4.0));
+    assert!(close(one_sample.p_value, 0.01324, 1e-5));
+
+    let greater = [1.0, 2.0, 3.0, 4.0, 5.0].t_test(0.0, Alternative::Greater)?;
+    assert!(close(greater.p_value, one_sample.p_value / 2.0, 1e-12));
+
+    let x: Vec<f64> = (1..=10).map(|v| v as f64).collect();
+    let y: Vec<f64> = (7..=20).map(|v| v as f64).collect();
+    let welch = x.welch_t_test(&y, Alternative::TwoSided)?;
+    assert!(close(welch.statistic, -5.4349, 1e-4));
+    assert!(close(welch.degrees_of_freedom.unwrap(), 21.982, 1e-3));
+    assert!(close(welch.p_value, 1.855e-5, 1e-8));
+
+    let pooled = x.two_sample_t_test(&y, Alternative::TwoSided)?;
+    assert_eq!(pooled.degrees_of_freedom, Some(22.0));
+
+    let drug_1 = [0.7, -1.6, -0.2, -1.2, -0.1, 3.4, 3.7, 0.8, 0.0, 2.0];
+    let drug_2 = [1.9, 0.8, 1.1, 0.1, -0.1, 4.4, 5.5, 1.6, 4.6, 3.4];
+    let paired = drug_1.paired_t_test(&drug_2[..], Alternative::TwoSided)?;
+    assert!(close(paired.statistic, -4.0621, 1e-4));
+    assert!(close(paired.p_value, 0.002833, 1e-6));
+    assert!(paired.reject(0.01));
+
+    let unpaired = drug_1.welch_t_test(&drug_2[..], Alternative::TwoSided)?;
+    assert!(close(unpaired.statistic, -1.8608, 1e-4));
+    assert!(close(unpaired.p_value, 0.07939, 1e-5));
+    assert!(!unpaired.reject(0.05));
+    assert!(drug_1.paired_t_test(&y, Alternative::TwoSided).is_err());
+
+    let f = x.f_test(&y, Alternative::TwoSided)?;
+    assert!(close(f.statistic, 9.1667 / 17.5, 1e-4));
+    assert_eq!(f.degrees_of_freedom, Some(9.0));
+    assert_eq!(f.denominator_degrees_of_freedom, Some(13.0));
+    assert!(close(
+        f.p_value,
+        y.f_test(&x, Alternative::TwoSided)?.p_value,
+        1e-9
+    ));
+
+    // Weights of 11 men from Shapiro and Wilk (1965).
+    let weights = [
+        148.0, 154.0, 158.0, 160.0, 161.0, 162.0, 166.0, 170.0, 182.0, 195.0, 236.0,
+    ];
+    let shapiro_wilk = weights.shapiro_wilk()?;
+    assert!(close(shapiro_wilk.statistic, 0.7888, 1e-3));
+    assert!(shapiro_wilk.reject(0.01));
+    assert!(x.shapiro_wilk()?.p_value > 0.5);
+    assert!([1.0, 2.0, 3.0].shapiro_wilk()?.p_value > 0.99);
+
+    let mut rng = StdRng::seed_from_u64(40);
+    let normal = Normal::new(0.0, 1.0).map_err(hypothesis::Error::Distribution)?;
+    let draws = normal.sample_n(&mut rng, 500);
+    let exponential = Exponential::new(1.0)
+        .map_err(hypothesis::Error::Distribution)?
+        .sample_n(&mut rng, 500);
+
+    assert!(!draws.jarque_bera()?.reject(0.01));
+    assert!(exponential.jarque_bera()?.reject(0.01));
+    assert_eq!(draws.jarque_bera()?.degrees_of_freedom, Some(2.0));
+    assert!(!draws.shapiro_wilk()?.reject(0.01));
+    assert!(exponential.shapiro_wilk()?.reject(0.01));
+    assert!(!draws.kolmogorov_smirnov(&normal)?.reject(0.01));
+    assert!(exponential.kolmogorov_smirnov(&normal)?.reject(0.01));
+    assert!(draws
+        .two_sample_kolmogorov_smirnov(&exponential)?
+        .reject(0.01));
+    assert!(close(
+        draws.two_sample_kolmogorov_smirnov(&draws)?.statistic,
+        0.0,
+        1e-12
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_confidence_intervals() -> Result<(), inference::Error> {
+    let close = |a: f64, b: f64, tolerance: f64| (a - b).abs() < tolerance;
+
+    // Reference interval from R's t.test(1:5).
+    let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+    let mean = values.mean_confidence_interval(0.95)?;
+    assert!(close(mean.lower, 1.036757, 1e-5));
+    assert!(close(mean.upper, 4.963243, 1e-5));
+    assert!(mean.contains(3.0));
+
+    let variance = values.variance_confidence_interval(0.95)?;
+    assert_eq!(variance.estimate, 2.5);
+    assert!(close(variance.lower, 10.0 / 11.143287, 1e-5));
+    assert!(close(variance.upper, 10.0 / 0.484419, 1e-4));
+    assert!(values.mean_confidence_interval(1.0).is_err());
+
+    let mut rng = StdRng::seed_from_u64(41);
+    let returns = Normal::new(0.001, 0.02)
+        .map_err(inference::Error::Distribution)?
+        .sample_n(&mut rng, 1000);
+
+    let daily = returns.sharpe_ratio_confidence_interval(0.0, 1, 0, 0.95)?;
+    assert!(close(daily.estimate, returns.sharpe_ratio(0.0), 1e-12));
+    assert!(close(
+        daily.width(),
+        2.0 * 1.959964 * ((1.0 + daily.estimate.powi(2) / 2.0) / 1000.0).sqrt(),
+        1e-5
+    ));
+
+    // Without serial correlation Lo's adjustment reduces to the square-root-of-time rule.
+    let annual = returns.sharpe_ratio_confidence_interval(0.0, 252, 0, 0.95)?;
+    assert!(close(annual.estimate, daily.estimate * 252f64.sqrt(), 1e-9));
+    assert!(close(annual.width(), daily.width() * 252f64.sqrt(), 1e-9));
+    let adjusted = returns.sharpe_ratio_confidence_interval(0.0, 252, 5, 0.95)?;
+    assert!(adjusted.estimate.is_finite() && adjusted.estimate != annual.estimate);
+
+    // The bootstrap interval of the mean agrees with the parametric interval.
+    let bootstrap = Bootstrap::new(2000, 7);
+    let parametric = returns.mean_confidence_interval(0.95)?;
+    let resampled = bootstrap.percentile_interval(&returns, |v| v.population_mean(), 0.95)?;
+    assert!(close(resampled.estimate, parametric.estimate, 1e-12));
+    assert!(close(resampled.lower, parametric.lower, 2e-4));
+    assert!(close(resampled.upper, parametric.upper, 2e-4));
+    assert!(close(
+        bootstrap.standard_error(&returns, |v| v.population_mean())?,
+        returns.sample_std_dev() / 1000f64.sqrt(),
+        1e-4
+    ));
+
+    // Resampling is reproducible for a given seed.
+    let sharpe = |v: &[f64]| v.sharpe_ratio(0.0);
+    let blocks = Bootstrap::new(500, 7).with_stationary_blocks(10.0);
+    assert_eq!(
+        blocks.replicates(&returns, sharpe)?,
+        blocks.replicates(&returns, sharpe)?
+    );
+    assert!(blocks
+        .percentile_interval(&returns, sharpe, 0.9)?
+        .contains(daily.estimate));
+    assert!(bootstrap
+        .with_stationary_blocks(0.5)
+        .replicates(&returns, sharpe)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_linear_regression() -> Result<(), regression::Error> {
+    let close = |a: f64, b: f64, tolerance: f64| (a - b).abs() < tolerance;
+
+    let x = [1.0, 2.0, 3.0, 4.0, 5.0];
+    let y = [2.0, 4.0, 5.0, 4.0, 5.0];
+    let design: Vec<Vec<f64>> = x.iter().map(|v| vec![*v]).collect();
+
+    let ols = LinearRegression::new().fit(&y[..], &design)?;
+    assert!(close(ols.coefficients[0], 2.2, 1e-12));
+    assert!(close(ols.coefficients[1], 0.6, 1e-12));
+    assert!(close(ols.standard_errors[0], 0.88f64.sqrt(), 1e-12));
+    assert!(close(ols.standard_errors[1], 0.08f64.sqrt(), 1e-12));
+    assert!(close(ols.t_statistics[1], 2.12132, 1e-5));
+    assert!(close(ols.p_values[1], 0.1240, 1e-4));
+    assert!(close(ols.r_squared, 0.6, 1e-12));
+    assert!(close(ols.adjusted_r_squared, 1.0 - 0.4 * 4.0 / 3.0, 1e-12));
+    assert_eq!(ols.degrees_of_freedom, 3);
+    assert!(close(ols.residuals[2], 1.0, 1e-12));
+    assert!(close(ols.predict(&[6.0]), 5.8, 1e-12));
+
+    // Zero Newey-West lags gives White's heteroskedasticity-consistent standard errors.
+    let white = LinearRegression::new()
+        .with_newey_west(Some(0))
+        .fit(&y[..], &design)?;
+    assert_eq!(white.coefficients, ols.coefficients);
+    assert!(close(white.standard_errors[1], 0.0344f64.sqrt(), 1e-12));
+
+    // Integer weights are equivalent to repeating observations.
+    let wls = LinearRegression::new()
+        .with_weights(vec![1.0, 2.0, 1.0, 3.0, 1.0])
+        .fit(&y[..], &design)?;
+    let repeated_x = [1.0, 2.0, 2.0, 3.0, 4.0, 4.0, 4.0, 5.0];
+    let repeated_y = [2.0, 4.0, 4.0, 5.0, 4.0, 4.0, 4.0, 5.0];
+    let repeated_design: Vec<Vec<f64>> = repeated_x.iter().map(|v| vec![*v]).collect();
+    let repeated = LinearRegression::new().fit(&repeated_y[..], &repeated_design)?;
+    assert!(close(wls.coefficients[0], repeated.coefficients[0], 1e-12));
+    assert!(close(wls.coefficients[1], repeated.coefficients[1], 1e-12));
+    assert!(close(wls.r_squared, repeated.r_squared, 1e-12));
+
+    let through_origin = LinearRegression::new()
+        .without_intercept()
+        .fit(&y[..], &design)?;
+    assert!(close(through_origin.coefficients[0], 66.0 / 55.0, 1e-12));
+
+    let collinear: Vec<Vec<f64>> = x.iter().map(|v| vec![*v, 2.0 * v]).collect();
+    assert!(LinearRegression::new().fit(&y[..], &collinear).is_err());
+    assert!(LinearRegression::new().fit(&y[..2], &design[..2]).is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_regression_on_returns() -> Result<(), Box<dyn std::error::Error>> {
+    let close = |a: f64, b: f64, tolerance: f64| (a - b).abs() < tolerance;
+
+    // A CAPM-style beta matches the ratio of covariance to variance.
+    let portfolio = Portfolio::load(PORTFOLIO_PATH.into())?;
+    let returns = portfolio.asset_returns();
+    let (apple, microsoft) = (&returns["AAPL"], &returns["MSFT"]);
+
+    let capm = LinearRegression::new()
+        .with_newey_west(None)
+        .fit_series(apple, &[microsoft])?;
+    assert!(close(
+        capm.coefficients[1],
+        apple.covariance(microsoft) / microsoft.sample_variance(),
+        1e-9
+    ));
+    assert!(close(
+        capm.r_squared,
+        apple.correlation(microsoft).powi(2),
+        1e-9
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_serial_dependence() -> Result<(), Box<dyn std::error::Error>> {
+    let close = |a: f64, b: f64, tolerance: f64| (a - b).abs() < tolerance;
+
+    let trend = [1.0, 2.0, 3.0, 4.0, 5.0];
+    let acf = trend.try_autocorrelations(2)?;
+    assert!(close(acf[0], 0.4, 1e-12));
+    assert!(close(acf[1], -0.1, 1e-12));
+
+    let pacf = trend.try_partial_autocorrelations(2)?;
+    assert!(close(pacf[0], 0.4, 1e-12));
+    assert!(close(pacf[1], -0.26 / 0.84, 1e-12));
+
+    let ljung_box = trend.ljung_box(2, 0)?;
+    assert!(close(
+        ljung_box.statistic,
+        35.0 * (0.16 / 4.0 + 0.01 / 3.0),
+        1e-12
+    ));
+    assert_eq!(ljung_box.degrees_of_freedom, Some(2.0));
+    assert!(close(trend.box_pierce(2, 0)?.statistic, 5.0 * 0.17, 1e-12));
+    assert!(trend.try_autocorrelations(5).is_err());
+    assert!(trend.ljung_box(2, 2).is_err());
+
+    // White noise, an AR(1) process with coefficient 0.6, and a random walk.
+    let mut rng = StdRng::seed_from_u64(43);
+    let noise = Normal::new(0.0, 1.0)?.sample_n(&mut rng, 1000);
+    let mut autoregressive = vec![0.0];
+    let mut random_walk = vec![0.0];
+
+    for e in &noise[1..] {
+        autoregressive.push(0.6 * autoregressive.last().unwrap() + e);
+        random_walk.push(random_walk.last().unwrap() + e);
+    }
+
+    let lags = time_series::suggested_lags(noise.len());
+    assert_eq!(lags, 10);
+    assert!(!noise.ljung_box(lags, 0)?.reject(0.01));
+    assert!(autoregressive.ljung_box(lags, 0)?.reject(0.01));
+    assert!(close(
+        autoregressive.partial_autocorrelations(2)[0],
+        0.6,
+        0.05
+    ));
+    assert!(autoregressive.partial_autocorrelations(2)[1].abs() < 0.1);
+
+    let stationary = autoregressive.augmented_dickey_fuller(
+        Deterministic::Constant,
+        LagSelection::Aic { max_lags: None },
+    )?;
+    assert!(stationary.p_value < 0.01);
+    assert!(stationary.statistic < stationary.critical_values[0]);
+    assert!(close(stationary.critical_values[1], -2.864, 1e-3));
+
+    let unit_root = random_walk.augmented_dickey_fuller(
+        Deterministic::ConstantTrend,
+        LagSelection::Bic { max_lags: Some(8) },
+    )?;
+    assert!(unit_root.p_value > 0.1);
+    assert!(unit_root.lags <= 8);
+
+    let fixed = random_walk.augmented_dickey_fuller(Deterministic::None, LagSelection::Fixed(0))?;
+    assert_eq!(fixed.lags, 0);
+    assert_eq!(fixed.observations, 999);
+
+    // Lag selection is limited to the lags a short series supports.
+    let short = [1.0, 2.0, 1.5];
+    assert!(matches!(
+        short.augmented_dickey_fuller(
+            Deterministic::Constant,
+            LagSelection::Aic { max_lags: None }
+        ),
+        Err(time_series::Error::Stats(
+            StatsError::InsufficientData { .. }
+        ))
+    ));
+    let without_constant =
+        short.augmented_dickey_fuller(Deterministic::None, LagSelection::Aic { max_lags: None })?;
+    assert_eq!(without_constant.lags, 0);
+
+    // A constant needs four observations, the fewest that leave a residual degree of freedom.
+    assert!(matches!(
+        short.augmented_dickey_fuller(Deterministic::Constant, LagSelection::Fixed(0)),
+        Err(time_series::Error::Stats(StatsError::InsufficientData {
+            required: 4,
+            actual: 3
+        }))
+    ));
+    let boundary = [1.0, 2.0, 1.5, 1.8];
+    let constant =
+        boundary.augmented_dickey_fuller(Deterministic::Constant, LagSelection::Fixed(0))?;
+    assert_eq!(constant.observations, 3);
+    assert!(constant.statistic.is_finite());
+    assert!(matches!(
+        boundary.augmented_dickey_fuller(Deterministic::Constant, LagSelection::Fixed(1)),
+        Err(time_series::Error::InvalidLag(1))
+    ));
+
+    let fifty = &random_walk[..50];
+    assert!(matches!(
+        fifty.augmented_dickey_fuller(
+            Deterministic::Constant,
+            LagSelection::Bic { max_lags: Some(60) }
+        ),
+        Err(time_series::Error::InvalidLag(60))
+    ));
+    assert!(fifty
+        .augmented_dickey_fuller(
+            Deterministic::ConstantTrend,
+            LagSelection::Bic { max_lags: None }
+        )
+        .is_ok());
+
+    let random = noise.variance_ratio(4, true)?;
+    assert!(close(random.ratio, 1.0, 0.15));
+    assert!(random.p_value > 0.01);
+    let trending = autoregressive.variance_ratio(4, false)?;
+    assert!(trending.ratio > 1.5);
+    assert!(trending.p_value < 0.01);
+    assert!(noise.variance_ratio(1, true).is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_price_stationarity() -> Result<(), Box<dyn std::error::Error>> {
+    let portfolio = Portfolio::load(PORTFOLIO_PATH.into())?;
+
+    for asset in portfolio.assets.values() {
+        let prices: Vec<f64> = asset
+            .stock_data
+            .closing_prices()
+            .iter()
+            .map(|p| p.ln())
+            .collect();
+        let returns = asset.stock_data.returns();
+
+        let prices_test = prices.augmented_dickey_fuller(
+            Deterministic::Constant,
+            LagSelection::Aic { max_lags: None },
+        )?;
+        let returns_test = Returns(returns.0[1..].to_vec()).augmented_dickey_fuller(
+            Deterministic::Constant,
+            LagSelection::Aic { max_lags: None },
+        )?;
+
+        assert!(returns_test.p_value < prices_test.p_value);
+        assert!(returns_test.p_value < 0.01);
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn test_arima_models() -> Result<(), Box<dyn std::error::Error>> {
+    let close = |a: f64, b: f64, tolerance: f64| (a - b).abs() < tolerance;
+
+    let mut rng = StdRng::seed_from_u64(47);
+    let noise = Normal::new(0.0, 1.0)?.sample_n(&mut rng, 2000);
+
+    // AR(1) with constant 0.5 and coefficient 0.6, so a long-run mean of 1.25.
+    let mut autoregressive = vec![1.25];
+    // MA(1) with mean 0.1 and coefficient 0.4.
+    let mut moving_average = vec![0.1];
+    // A random walk with drift 0.2.
+    let mut random_walk = vec![0.0];
+
+    for t in 1..noise.len() {
+        autoregressive.push(0.5 + 0.6 * autoregressive[t - 1] + noise[t]);
+        moving_average.push(0.1 + noise[t] + 0.4 * noise[t - 1]);
+        random_walk.push(random_walk[t - 1] + 0.2 + noise[t]);
+    }
+
+    let ar = Arima::autoregressive(&autoregressive, 1)?;
+    assert!(close(ar.ar[0], 0.6, 0.05));
+    assert!(close(ar.mean(), 1.25, 0.15));
+    assert!(close(ar.variance, 1.0, 0.1));
+    assert_eq!(ar.residuals.len(), 1999);
+
+    let ma = Arima::moving_average(&moving_average, 1)?;
+    assert!(close(ma.ma[0], 0.4, 0.05));
+    assert!(close(ma.constant, 0.1, 0.05));
+    assert!(ma.log_likelihood > Arima::moving_average(&moving_average, 0)?.log_likelihood);
+
+    let selected = Arima::select(&autoregressive, 3, 0, 2, true, InformationCriterion::Bic)?;
+    assert_eq!(selected.order, ArimaOrder::new(1, 0, 0));
+    assert!(selected.bic <= ar.bic);
+
+    // Forecasts revert to the long-run mean, with widening prediction intervals.
+    let forecasts = ar.forecast(50, 0.95)?;
+    assert_eq!(forecasts.len(), 50);
+    assert!(close(forecasts[49].interval.estimate, ar.mean(), 1e-6));
+    assert!(close(
+        forecasts[0].standard_error,
+        ar.variance.sqrt(),
+        1e-12
+    ));
+    assert!(forecasts
+        .windows(2)
+        .all(|f| f[1].interval.width() >= f[0].interval.width()));
+    assert!(close(
+        forecasts[49].standard_error,
+        (ar.variance / (1.0 - ar.ar[0].powi(2))).sqrt(),
+        1e-6
+    ));
+
+    // A random walk's forecast error grows with the square root of the horizon.
+    let walk = Arima::fit(&random_walk, ArimaOrder::new(0, 1, 0), true)?;
+    assert!(close(walk.constant, 0.2, 0.05));
+    let walk_forecasts = walk.forecast(4, 0.95)?;
+    assert!(close(
+        walk_forecasts[3].standard_error,
+        2.0 * walk_forecasts[0].standard_error,
+        1e-12
+    ));
+    assert!(close(
+        walk_forecasts[3].interval.estimate,
+        random_walk[1999] + 4.0 * walk.constant,
+        1e-9
+    ));
+
+    let integrated = Arima::fit(&random_walk, ArimaOrder::new(1, 1, 1), true)?;
+    assert!(integrated.forecast(10, 0.9)?[9]
+        .interval
+        .contains(random_walk[1999] + 2.0));
+    assert!(ar.forecast(5, 1.0).is_err());
+    assert!(Arima::fit(&noise[..3], ArimaOrder::new(2, 0, 2), true).is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_price_forecasts() -> Result<(), Box<dyn std::error::Error>> {
+    let portfolio = Portfolio::load(PORTFOLIO_PATH.into())?;
+
+    for asset in portfolio.assets.values() {
+        let prices = asset.stock_data.closing_prices();
+        let model = Arima::select(&prices, 2, 1, 2, false, InformationCriterion::Aic)?;
+        let forecasts = model.forecast(5, 0.95)?;
+
+        assert_eq!(model.order.d, 1);
+        assert!(forecasts[0].interval.contains(prices[prices.len() - 1]));
+    }
+
+    Ok(())
+}
+
+/// Simulate returns from a GARCH(1, 1)-family process
+fn simulate_garch(
+    model: VarianceModel,
+    (omega, alpha, beta, gamma): (f64, f64, f64, f64),
+    innovations: &[f64],
+) -> Vec<f64> {
+    let mut variance = match model {
+        VarianceModel::Egarch => (omega / (1.0 - beta)).exp(),
+        VarianceModel::Garch => omega / (1.0 - alpha - beta),
+        VarianceModel::GjrGarch => omega / (1.0 - alpha - gamma / 2.0 - beta),
+    };
+    let mut returns = Vec::with_capacity(innovations.len());
+
+    for z in innovations {
+        let e = variance.sqrt() * z;
+        returns.push(e);
+
+        variance = match model {
+            VarianceModel::Garch => omega + alpha * e * e + beta * variance,
+            VarianceModel::GjrGarch => {
+                omega + (alpha + if e < 0.0 { gamma } else { 0.0 }) * e * e + beta * variance
+            }
+            VarianceModel::Egarch => (omega
+                + alpha * (z.abs() - (2.0 / std::f64::consts::PI).sqrt())
+                + gamma * z
+                + beta * variance.ln())
+            .exp(),
+        };
+    }
+
+    returns
+}
+
+#[test]
+pub fn test_garch_models() -> Result<(), Box<dyn std::error::Error>> {
+    let close = |a: f64, b: f64, tolerance: f64| (a - b).abs() < tolerance;
+
+    let mut rng = StdRng::seed_from_u64(45);
+    let innovations = Normal::new(0.0, 1.0)?.sample_n(&mut rng, 3000);
+
+    let returns = simulate_garch(VarianceModel::Garch, (2e-6, 0.08, 0.9, 0.0), &innovations);
+    let garch = Garch::fit(&returns, VarianceModel::Garch, Innovation::Normal)?;
+    assert!(close(garch.alpha, 0.08, 0.03));
+    assert!(close(garch.beta, 0.9, 0.04));
+    assert!(close(garch.unconditional_variance(), 1e-4, 3e-5));
+    assert_eq!(garch.conditional_variance.len(), returns.len());
+
+    // Forecasts start from the next conditional variance and revert to the long-run variance.
+    let last = returns.len() - 1;
+    let forecasts = garch.forecast_variance(5000);
+    assert!(close(
+        forecasts[0],
+        garch.omega
+            + garch.alpha * (returns[last] - garch.mu).powi(2)
+            + garch.beta * garch.conditional_variance[last],
+        1e-15
+    ));
+    assert!(close(forecasts[4999], garch.unconditional_variance(), 1e-9));
+    assert!(close(
+        garch.forecast_volatility(1, TRADING_DAYS_PER_YEAR)[0],
+        (forecasts[0] * TRADING_DAYS_PER_YEAR).sqrt(),
+        1e-12
+    ));
+    assert!(close(
+        garch.forecast_horizon_volatility(10),
+        forecasts[..10].iter().sum::<f64>().sqrt(),
+        1e-12
+    ));
+
+    let leveraged = simulate_garch(
+        VarianceModel::GjrGarch,
+        (2e-6, 0.02, 0.88, 0.12),
+        &innovations,
+    );
+    let gjr = Garch::fit(&leveraged, VarianceModel::GjrGarch, Innovation::Normal)?;
+    assert!(close(gjr.gamma, 0.12, 0.05));
+    assert!(
+        gjr.log_likelihood
+            > Garch::fit(&leveraged, VarianceModel::Garch, Innovation::Normal)?.log_likelihood
+    );
+
+    let exponential = simulate_garch(
+        VarianceModel::Egarch,
+        (-0.4, 0.15, 0.95, -0.08),
+        &innovations,
+    );
+    let egarch = Garch::fit(&exponential, VarianceModel::Egarch, Innovation::Normal)?;
+    assert!(close(egarch.beta, 0.95, 0.03));
+    assert!(close(egarch.gamma, -0.08, 0.04));
+    assert!(egarch
+        .forecast_variance(5)
+        .iter()
+        .all(|v| v.is_finite() && *v > 0.0));
+
+    // Fat-tailed innovations are detected with Student's t likelihood.
+    let t = StudentT::standard(5.0)?;
+    let fat_tails: Vec<f64> = t
+        .sample_n(&mut rng, 3000)
+        .iter()
+        .map(|z| z * (3.0f64 / 5.0).sqrt())
+        .collect();
+    let returns = simulate_garch(VarianceModel::Garch, (2e-6, 0.08, 0.9, 0.0), &fat_tails);
+    let student = Garch::fit(&returns, VarianceModel::Garch, Innovation::StudentT)?;
+    let nu = student.degrees_of_freedom.unwrap_or(f64::NAN);
+    assert!(close(nu, 5.0, 1.5));
+    assert!(student.aic < Garch::fit(&returns, VarianceModel::Garch, Innovation::Normal)?.aic);
+
+    assert!(Garch::fit(&returns[..10], VarianceModel::Garch, Innovation::Normal).is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_conditional_volatility() -> Result<(), Box<dyn std::error::Error>> {
+    let portfolio = Portfolio::load(PORTFOLIO_PATH.into())?;
+
+    for asset in portfolio.assets.values() {
+        let returns = Returns(asset.stock_data.returns().0[1..].to_vec());
+        let model = Garch::fit(&returns, VarianceModel::GjrGarch, Innovation::StudentT)?;
+
+        assert!(model.persistence() < 1.0);
+        assert!(model.conditional_variance.iter().all(|v| *v > 0.0));
+        assert!(model.forecast_volatility(10, TRADING_DAYS_PER_YEAR)[9] > 0.0);
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn test_realized_volatility() -> Result<(), Box<dyn std::error::Error>> {
+    let close = |a: f64, b: f64, tolerance: f64| (a - b).abs() < tolerance;
+    let estimate = |stock_data: &StockData, estimator: VolatilityEstimator| {
+        realized_volatility::volatility(stock_data, estimator, TRADING_DAYS_PER_YEAR)
+    };
+
+    let portfolio = Portfolio::load(PORTFOLIO_PATH.into())?;
+    let apple = &portfolio.assets["AAPL"].stock_data;
+
+    // Close-to-close volatility matches the annualized standard deviation of log returns.
+    let log_returns: Vec<f64> = apple
+        .closing_prices()
+        .windows(2)
+        .map(|w| (w[1] / w[0]).ln())
+        .collect();
+    assert!(close(
+        estimate(apple, VolatilityEstimator::CloseToClose)?,
+        log_returns.sample_std_dev() * TRADING_DAYS_PER_YEAR.sqrt(),
+        1e-12
+    ));
+
+    let parkinson_variance = apple
+        .data
+        .iter()
+        .map(|d| (d.high / d.low).ln().powi(2))
+        .sum::<f64>()
+        / (4.0 * 2f64.ln() * apple.data.len() as f64);
+    assert!(close(
+        estimate(apple, VolatilityEstimator::Parkinson)?,
+        (parkinson_variance * TRADING_DAYS_PER_YEAR).sqrt(),
+        1e-12
+    ));
+
+    // The range-based estimators agree broadly with close-to-close volatility.
+    for asset in portfolio.assets.values() {
+        let close_to_close = estimate(&asset.stock_data, VolatilityEstimator::CloseToClose)?;
+
+        for estimator in [
+            VolatilityEstimator::Parkinson,
+            VolatilityEstimator::GarmanKlass,
+            VolatilityEstimator::RogersSatchell,
+            VolatilityEstimator::YangZhang,
+        ] {
+            let volatility = estimate(&asset.stock_data, estimator)?;
+            assert!(volatility > 0.5 * close_to_close && volatility < 1.5 * close_to_close);
+        }
+    }
+
+    // The last rolling estimate matches the estimate over the last window of bars.
+    let window = Window::new(21);
+    let rolling = realized_volatility::rolling_volatility(
+        apple,
+        VolatilityEstimator::YangZhang,
+        window,
+        TRADING_DAYS_PER_YEAR,
+    )?;
+    let last_month = StockData {
+        data: apple.data[apple.data.len() - 22..].to_vec(),
+        ..Default::default()
+    };
+
+    assert_eq!(rolling.len(), apple.data.len());
+    assert!(rolling[..21].iter().all(|v| v.is_nan()));
+    assert!(!rolling[21].is_nan());
+    assert!(close(
+        rolling[rolling.len() - 1],
+        estimate(&last_month, VolatilityEstimator::YangZhang)?,
+        1e-12
+    ));
+
+    let mut invalid = last_month.clone();
+    invalid.data[3].low = invalid.data[3].high + 1.0;
+    assert!(estimate(&invalid, VolatilityEstimator::Parkinson).is_err());
+
+    // Highs and lows must contain the open and close, or the variance could be negative.
+    let mut invalid = last_month.clone();
+    invalid.data[3].high = invalid.data[3].open.max(invalid.data[3].close) * 0.99;
+    assert!(matches!(
+        realized_volatility::volatility(
+            &invalid,
+            VolatilityEstimator::RogersSatchell,
+            TRADING_DAYS_PER_YEAR
+        ),
+        Err(realized_volatility::Error::InvalidBar(3))
+    ));
+    let mut invalid = last_month.clone();
+    invalid.data[5].low = invalid.data[5].open.min(invalid.data[5].close) * 1.01;
+    assert!(estimate(&invalid, VolatilityEstimator::YangZhang).is_err());
+    assert!(estimate(&StockData::default(), VolatilityEstimator::GarmanKlass).is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_performance_ratios() -> Result<(), StatsError> {
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+
+    let returns = Returns(vec![0.1, -0.05, 0.02, -0.1, 0.05]);
+    let benchmark = Returns(vec![0.05, -0.02, 0.01, -0.06, 0.03]);
+
+    // Shortfalls of 0.05 and 0.1 below a zero target, averaged over all five periods.
+    assert!(close(returns.target_semideviation(0.0, 1.0)?, 0.05));
+    assert!(close(
+        returns.target_semideviation(0.0, TRADING_DAYS_PER_YEAR)?,
+        0.05 * TRADING_DAYS_PER_YEAR.sqrt()
+    ));
+    assert!(close(returns.sortino_ratio(0.0, 1.0)?, 0.08));
+    assert!(close(
+        returns.sortino_ratio(0.0, TRADING_DAYS_PER_YEAR)?,
+        0.08 * TRADING_DAYS_PER_YEAR.sqrt()
+    ));
+    assert!(close(returns.omega_ratio(0.0)?, 0.17 / 0.15));
+
+    // A beta of 1.826 to the benchmark.
+    assert!(close(
+        returns.treynor_ratio(&benchmark, 0.001, 1.0)?,
+        0.0016427525622254757
+    ));
+    assert!(close(
+        returns.information_ratio(&benchmark, 1.0)?,
+        0.05403432269582989
+    ));
+    assert!(close(
+        returns.m_squared(&benchmark, 0.001, 1.0)?,
+        0.0026305728356966606
+    ));
+
+    // The wealth index peaks at 1.1 and falls to 0.95931 without recovering, so there is a
+    // single drawdown of 12.79%.
+    let growth = 0.0014508837597930757;
+    assert!(close(returns.annualized_return(1.0)?, growth));
+    assert!(close(returns.calmar_ratio(0.0, 1.0)?, growth / 0.1279));
+    assert!(close(returns.sterling_ratio(0.0, 1.0, 3)?, growth / 0.1279));
+    assert!(close(returns.burke_ratio(0.0, 1.0)?, growth / 0.1279));
+    assert!(close(returns.ulcer_index()?, 0.07338263694498867));
+
+    assert!(matches!(
+        Returns(vec![0.01, 0.02]).sortino_ratio(0.0, 1.0),
+        Err(StatsError::DegenerateVariance)
+    ));
+    assert!(matches!(
+        returns.information_ratio(&Returns(vec![0.01; 4]), 1.0),
+        Err(StatsError::LengthMismatch { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_drawdowns() -> Result<(), Box<dyn std::error::Error>> {
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+
+    let returns = Returns(vec![-0.05, 0.1, -0.1, 0.05, 0.1, -0.02, -0.03, 0.01]);
+    let analysis = Drawdowns::from_returns(&returns)?;
+
+    assert!(close(analysis.wealth_index[1], 1.045));
+    assert!(close(analysis.drawdowns[2], -0.1));
+    assert_eq!(analysis.drawdowns[4], 0.0);
+
+    // A drawdown from the initial value, a recovered drawdown and one still underwater.
+    assert_eq!(analysis.episodes.len(), 3);
+    let deepest = analysis.max_drawdown().ok_or("no drawdown")?;
+    assert_eq!(
+        (deepest.peak, deepest.trough, deepest.recovery),
+        (Some(1), 2, Some(4))
+    );
+    assert!(close(deepest.depth, 0.1));
+    assert_eq!(deepest.duration, 2);
+    assert_eq!(
+        (deepest.decline(), deepest.recovery_periods()),
+        (1, Some(2))
+    );
+
+    let first = &analysis.episodes[0];
+    assert_eq!(
+        (first.peak, first.trough, first.recovery),
+        (None, 0, Some(1))
+    );
+
+    let last = &analysis.episodes[2];
+    assert_eq!((last.peak, last.trough, last.recovery), (Some(4), 6, None));
+    assert!(close(last.depth, 0.0494));
+    assert_eq!((last.duration, last.recovery_periods()), (3, None));
+
+    assert!(close(
+        analysis.average_drawdown(),
+        (0.05 + 0.1 + 0.0494) / 3.0
+    ));
+    assert_eq!(analysis.max_duration(), 3);
+    assert!(close(analysis.average_duration(), 2.0));
+
+    let top = analysis.top(2);
+    assert_eq!(top.len(), 2);
+    assert!(close(top[0].depth, 0.1) && close(top[1].depth, 0.05));
+    assert_eq!(analysis.top(10).len(), 3);
+
+    assert_eq!(
+        drawdown::drawdown_series(&drawdown::wealth_index(&returns.0)),
+        analysis.drawdowns
+    );
+    assert!(Drawdowns::from_returns(&Returns(vec![0.01, 0.02]))?
+        .max_drawdown()
+        .is_none());
+    assert!(Drawdowns::from_returns(&Returns(vec![0.1, -1.0])).is_err());
+
+    // Drawdowns of a stock are dated by its bars.
+    let portfolio = Portfolio::load(PORTFOLIO_PATH.into())?;
+    let apple = &portfolio.assets["AAPL"].stock_data;
+    let analysis = Drawdowns::from_stock_data(apple)?;
+    let deepest = analysis.max_drawdown().ok_or("no drawdown")?;
+    let peak = deepest.peak.ok_or("no peak")?;
+
+    assert_eq!(analysis.wealth_index.len(), apple.data.len());
+    assert_eq!(deepest.peak_date, Some(apple.data[peak].date));
+    assert_eq!(deepest.trough_date, Some(apple.data[deepest.trough].date));
+    assert!(deepest.peak_date < deepest.trough_date);
+    assert!(close(
+        -deepest.depth,
+        analysis.drawdowns.iter().copied().fold(0.0, f64::min)
+    ));
+    assert_eq!(
+        deepest.recovery_date,
+        deepest.recovery.map(|i| apple.data[i].date)
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_value_at_risk() -> Result<(), value_at_risk::Error> {
+    let close = |a: f64, b: f64, tolerance: f64| (a - b).abs() < tolerance;
+
+    // Returns from -5% to 4.9% in steps of 0.1%.
+    let returns = Returns((0..100).map(|i| (i as f64 - 50.0) / 1000.0).collect());
+
+    // The 5% quantile interpolates between the fifth and sixth smallest returns, and the shortfall
+    // averages the five returns beyond it.
+    let historical = ValueAtRisk::new(VarMethod::Historical, 0.95).estimate(&returns)?;
+    assert!(close(historical.value_at_risk, 0.04505, 1e-12));
+    assert!(close(historical.expected_shortfall, 0.048, 1e-12));
+    assert!(close(historical.scale(1_000.0).value_at_risk, 45.05, 1e-9));
+
+    let mean = returns.population_mean();
+    let std_dev = returns.sample_std_dev();
+    let normal = ValueAtRisk::new(VarMethod::Normal, 0.95).estimate(&returns)?;
+    assert!(close(normal.value_at_risk, 1.644854 * std_dev - mean, 1e-6));
+    assert!(close(
+        normal.expected_shortfall,
+        2.062713 * std_dev - mean,
+        1e-6
+    ));
+
+    // Mean and variance scale with the horizon.
+    let ten_day = ValueAtRisk::new(VarMethod::Normal, 0.95)
+        .with_horizon(10)
+        .estimate(&returns)?;
+    assert!(close(
+        ten_day.value_at_risk,
+        1.644854 * std_dev * 10f64.sqrt() - 10.0 * mean,
+        1e-6
+    ));
+
+    // Normal returns have no Cornish-Fisher adjustment, and simulations converge on the normal
+    // estimate.
+    let mut rng = StdRng::seed_from_u64(49);
+    let gaussian = Returns(Normal::new(0.001, 0.02)?.sample_n(&mut rng, 20_000));
+    let normal = ValueAtRisk::new(VarMethod::Normal, 0.99).estimate(&gaussian)?;
+    let cornish_fisher = ValueAtRisk::new(VarMethod::CornishFisher, 0.99).estimate(&gaussian)?;
+    let monte_carlo = ValueAtRisk::new(
+        VarMethod::MonteCarlo {
+            simulations: 50_000,
+            seed: 7,
+        },
+        0.99,
+    )
+    .estimate(&gaussian)?;
+    assert!(close(
+        cornish_fisher.value_at_risk,
+        normal.value_at_risk,
+        1e-3
+    ));
+    assert!(close(
+        cornish_fisher.expected_shortfall,
+        normal.expected_shortfall,
+        1e-3
+    ));
+    assert!(close(monte_carlo.value_at_risk, normal.value_at_risk, 1e-3));
+    assert!(close(
+        monte_carlo.expected_shortfall,
+        normal.expected_shortfall,
+        1.5e-3
+    ));
+
+    assert!(matches!(
+        ValueAtRisk::new(VarMethod::Normal, 1.0).estimate(&returns),
+        Err(value_at_risk::Error::InvalidConfidence(_))
+    ));
+    assert!(matches!(
+        ValueAtRisk::new(VarMethod::Historical, 0.95)
+            .with_horizon(0)
+            .estimate(&returns),
+        Err(value_at_risk::Error::InvalidHorizon)
+    ));
+
+    // A 99% VaR with 8 exceptions in 250 days, of which 4 follow another exception.
+    let mut realized = vec![0.0; 250];
+    for i in [10, 11, 50, 51, 52, 100, 200, 201] {
+        realized[i] = -0.02;
+    }
+    let forecasts = vec![0.01; 250];
+
+    let kupiec = value_at_risk::kupiec_test(&realized, &forecasts, 0.99)?;
+    assert!(close(kupiec.statistic, 7.7335507244945205, 1e-9));
+    assert!(close(kupiec.p_value, 0.005420405194127794, 1e-6));
+
+    let christoffersen = value_at_risk::christoffersen_test(&realized, &forecasts, 0.99)?;
+    assert!(close(
+        christoffersen.independence.statistic,
+        18.936740785607732,
+        1e-9
+    ));
+    assert!(close(
+        christoffersen.conditional_coverage.statistic,
+        26.670291510102253,
+        1e-9
+    ));
+    assert!(close(
+        christoffersen.conditional_coverage.p_value,
+        1.616664058390287e-06,
+        1e-8
+    ));
+    assert!(christoffersen.independence.reject(0.01));
+
+    // The expected number of exceptions is not rejected.
+    let mut realized = vec![0.0; 100];
+    for i in [5, 25, 45, 65, 85] {
+        realized[i] = -0.02;
+    }
+    let kupiec = value_at_risk::kupiec_test(&realized, &[0.01; 100], 0.95)?;
+    assert!(close(kupiec.statistic, 0.0, 1e-12) && close(kupiec.p_value, 1.0, 1e-12));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_portfolio_value_at_risk() -> Result<(), Box<dyn std::error::Error>> {
+    let close = |a: f64, b: f64, tolerance: f64| (a - b).abs() < tolerance;
+    let estimate = |method: VarMethod, portfolio: &Portfolio| {
+        ValueAtRisk::new(method, 0.99)
+            .with_horizon(5)
+            .estimate_portfolio(portfolio)
+    };
+
+    let mut portfolio = Portfolio::load(PORTFOLIO_PATH.into())?;
+
+    assert!(estimate(VarMethod::Normal, &portfolio).is_err());
+
+    for (ticker, amount) in [("AAPL", 4_000.0), ("MSFT", 3_000.0), ("GOOG", 3_000.0)] {
+        portfolio
+            .assets
+            .get_mut(ticker)
+            .ok_or("missing asset")?
+            .amount_invested = amount;
+    }
+
+    let weights = portfolio.weights();
+    let tickers = portfolio.tickers();
+    let aapl = tickers
+        .iter()
+        .position(|t| t == "AAPL")
+        .ok_or("missing AAPL")?;
+    assert!(close(weights.iter().sum::<f64>(), 1.0, 1e-12));
+    assert!(close(weights[aapl], 0.4, 1e-12));
+
+    let aligned = portfolio.aligned_returns();
+    assert_eq!(
+        aligned.len(),
+        portfolio.assets["AAPL"].stock_data.data.len() - 1
+    );
+
+    // Historical estimates use the weighted returns of the portfolio.
+    let portfolio_returns = Returns(
+        aligned
+            .iter()
+            .map(|(_, r)| r.iter().zip(&weights).map(|(r, w)| r * w).sum())
+            .collect(),
+    );
+    let historical = estimate(VarMethod::Historical, &portfolio)?;
+    let expected = ValueAtRisk::new(VarMethod::Historical, 0.99)
+        .with_horizon(5)
+        .estimate(&portfolio_returns)?;
+    assert!(close(
+        historical.value_at_risk,
+        expected.value_at_risk,
+        1e-12
+    ));
+
+    // The covariance matrix gives the same variance as the weighted returns.
+    let normal = estimate(VarMethod::Normal, &portfolio)?;
+    let expected = ValueAtRisk::new(VarMethod::Normal, 0.99)
+        .with_horizon(5)
+        .estimate(&portfolio_returns)?;
+    assert!(close(normal.value_at_risk, expected.value_at_risk, 1e-12));
+    assert!(normal.expected_shortfall > normal.value_at_risk);
+
+    let cornish_fisher = estimate(VarMethod::CornishFisher, &portfolio)?;
+    assert!(cornish_fisher.expected_shortfall > cornish_fisher.value_at_risk);
+
+    // Correlated simulations agree with the normal estimate, up to compounding.
+    let monte_carlo = estimate(
+        VarMethod::MonteCarlo {
+            simulations: 20_000,
+            seed: 49,
+        },
+        &portfolio,
+    )?;
+    assert!(close(
+        monte_carlo.value_at_risk,
+        normal.value_at_risk,
+        0.005
+    ));
+
+    // The return across a missing bar spans two days, so it is left out.
+    let dates: Vec<_> = portfolio.assets["MSFT"]
+        .stock_data
+        .data
+        .iter()
+        .map(|bar| bar.date)
+        .collect();
+    portfolio
+        .assets
+        .get_mut("MSFT")
+        .ok_or("missing asset")?
+        .stock_data
+        .data
+        .remove(100);
+
+    let msft = tickers
+        .iter()
+        .position(|t| t == "MSFT")
+        .ok_or("missing MSFT")?;
+    let dated = portfolio.dated_asset_returns();
+    assert!(!dated[msft].contains_key(&dates[100]));
+    assert!(!dated[msft].contains_key(&dates[101]));
+    assert!(dated[aapl].contains_key(&dates[101]));
+    assert_eq!(portfolio.aligned_returns().len(), aligned.len() - 2);
+    assert_eq!(portfolio.returns().count(), aligned.len() - 2);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_covariance_matrices() -> Result<(), covariance::Error> {
+    let close = |a: f64, b: f64, tolerance: f64| (a - b).abs() < tolerance;
+    let labels = vec!["x".to_string(), "y".to_string(), "z".to_string()];
+    let columns = vec![
+        vec![1.0, 2.0, 3.0, 4.0, 6.0],
+        vec![2.0, 1.0, 4.0, 3.0, 5.0],
+        vec![0.0, 1.0, 0.0, 2.0, 1.0],
+    ];
+
+    let sample = CovarianceMatrix::estimate(labels.clone(), &columns, CovarianceMethod::Sample)?;
+    assert!(close(
+        sample.get("x", "y").unwrap_or(f64::NAN),
+        columns[0].covariance(&columns[1]),
+        1e-12
+    ));
+    assert!(close(
+        sample.correlation()[0][2],
+        columns[0].correlation(&columns[2]),
+        1e-12
+    ));
+    assert!(close(
+        sample.portfolio_variance(&[0.5, 0.3, 0.2])?,
+        (0..5)
+            .map(|t| 0.5 * columns[0][t] + 0.3 * columns[1][t] + 0.2 * columns[2][t])
+            .collect::<Vec<f64>>()
+            .sample_variance(),
+        1e-12
+    ));
+    assert!(sample.portfolio_variance(&[1.0]).is_err());
+
+    // Shrinkage towards the average variance, with the intensity estimated from the data.
+    let shrunk =
+        CovarianceMatrix::estimate(labels.clone(), &columns, CovarianceMethod::LedoitWolf)?;
+    assert!(close(
+        shrunk.shrinkage.unwrap_or(f64::NAN),
+        0.377699018538713,
+        1e-12
+    ));
+    assert!(close(shrunk.matrix[0][0], 2.5369770992366414, 1e-12));
+    assert!(close(shrunk.matrix[0][1], 1.244601962922574, 1e-12));
+    assert!(close(shrunk.matrix[0][2], 0.3982726281352237, 1e-12));
+    assert!(close(shrunk.matrix[2][2], 1.0434547437295527, 1e-12));
+
+    let ewma =
+        CovarianceMatrix::estimate(labels.clone(), &columns, CovarianceMethod::riskmetrics())?;
+    assert!(close(
+        ewma.matrix[1][1],
+        *columns[1]
+            .ewma_variance(RISKMETRICS_LAMBDA, 1)
+            .last()
+            .unwrap_or(&f64::NAN),
+        1e-12
+    ));
+    assert!(close(ewma.matrix[0][2], ewma.matrix[2][0], 1e-15));
+    assert!(matches!(
+        CovarianceMatrix::estimate(labels, &columns, CovarianceMethod::Ewma { lambda: 1.0 }),
+        Err(covariance::Error::InvalidLambda(_))
+    ));
+
+    // An inconsistent set of correlations has a negative eigenvalue.
+    let inconsistent = CovarianceMatrix {
+        labels: vec!["a".into(), "b".into(), "c".into()],
+        matrix: vec![
+            vec![1.0, 0.9, 0.1],
+            vec![0.9, 1.0, 0.9],
+            vec![0.1, 0.9, 1.0],
+        ],
+        shrinkage: None,
+    };
+    let (values, vectors) = matrix::symmetric_eigen(&inconsistent.matrix);
+    assert!(close(values.iter().sum::<f64>(), 3.0, 1e-12));
+    assert!(values[2] < 0.0);
+
+    for (k, value) in values.iter().enumerate() {
+        let vector: Vec<f64> = vectors.iter().map(|row| row[k]).collect();
+        let product = matrix::multiply_vector(&inconsistent.matrix, &vector);
+
+        for (a, v) in product.iter().zip(&vector) {
+            assert!(close(*a, value * v, 1e-12));
+        }
+    }
+
+    assert!(!inconsistent.is_positive_definite());
+    let repaired = inconsistent.nearest_positive_definite(1e-6);
+    assert!(repaired.is_positive_definite());
+    assert!(close(repaired.matrix[0][2], repaired.matrix[2][0], 1e-12));
+    assert!(matrix::symmetric_eigen(&repaired.matrix).0[2] > 1e-7);
+
+    // A positive definite matrix is unchanged.
+    let unchanged = shrunk.nearest_positive_definite(1e-6);
+    for (a, b) in unchanged
+        .matrix
+        .iter()
+        .flatten()
+        .zip(shrunk.matrix.iter().flatten())
+    {
+        assert!(close(*a, *b, 1e-12));
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn test_portfolio_covariance() -> Result<(), Box<dyn std::error::Error>> {
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-15;
+    let estimate = |portfolio: &Portfolio, method: CovarianceMethod, alignment: Alignment| {
+        CovarianceMatrix::from_portfolio(portfolio, method, alignment)
+    };
+
+    let mut portfolio = Portfolio::load(PORTFOLIO_PATH.into())?;
+    let returns = portfolio.asset_returns();
+    let aapl = Returns(returns["AAPL"].0[1..].to_vec());
+    let msft = Returns(returns["MSFT"].0[1..].to_vec());
+
+    let sample = estimate(
+        &portfolio,
+        CovarianceMethod::Sample,
+        Alignment::CompleteCase,
+    )?;
+    assert_eq!(sample.labels, portfolio.tickers());
+    assert!(close(
+        sample.get("AAPL", "MSFT").ok_or("missing")?,
+        aapl.covariance(&msft)
+    ));
+    assert!(sample.is_positive_definite());
+
+    let shrunk = estimate(
+        &portfolio,
+        CovarianceMethod::LedoitWolf,
+        Alignment::CompleteCase,
+    )?;
+    let shrinkage = shrunk.shrinkage.ok_or("no shrinkage")?;
+    assert!(shrinkage > 0.0 && shrinkage < 1.0);
+
+    let ewma = estimate(
+        &portfolio,
+        CovarianceMethod::riskmetrics(),
+        Alignment::CompleteCase,
+    )?;
+    assert!(close(
+        ewma.get("AAPL", "AAPL").ok_or("missing")?,
+        *aapl
+            .ewma_variance(RISKMETRICS_LAMBDA, 1)
+            .last()
+            .ok_or("empty")?
+    ));
+
+    // Removing bars from one asset drops those dates for every pair under complete-case
+    // alignment, but only for pairs with that asset under pairwise alignment.
+    let meta = portfolio.assets.get_mut("META").ok_or("missing META")?;
+    meta.stock_data.data.drain(100..120);
+
+    let complete = estimate(
+        &portfolio,
+        CovarianceMethod::Sample,
+        Alignment::CompleteCase,
+    )?;
+    let pairwise = estimate(&portfolio, CovarianceMethod::Sample, Alignment::Pairwise)?;
+    let full = pairwise.get("AAPL", "MSFT").ok_or("missing")?;
+
+    assert!(close(full, aapl.covariance(&msft)));
+    assert!(!close(complete.get("AAPL", "MSFT").ok_or("missing")?, full));
+    assert!(estimate(
+        &portfolio,
+        CovarianceMethod::LedoitWolf,
+        Alignment::Pairwise
+    )
+    .is_err());
+    assert!(estimate(
+        &Portfolio::default(),
+        CovarianceMethod::Sample,
+        Alignment::CompleteCase
+    )
+    .is_err());
+
+    Ok(())
+}
@@ -0,0 +1,470 @@
+use crate::distributions::{ChiSquared, ContinuousDistribution};
+use crate::hypothesis::TestResult;
+use crate::regression::{self, LinearRegression, RegressionResult};
+use crate::special;
+use crate::stats::{Statistics, StatsError};
+
+/// Error type for time series analysis
+#[derive(Debug)]
+pub enum Error {
+    /// The series is empty, too short, contains NaN, or has no variance
+    Stats(StatsError),
+    /// The test regression could not be fitted
+    Regression(regression::Error),
+    /// Lags must be at least one and fewer than the length of the series supports
+    InvalidLag(usize),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<StatsError> for Error {
+    fn from(e: StatsError) -> Self {
+        Error::Stats(e)
+    }
+}
+
+impl From<regression::Error> for Error {
+    fn from(e: regression::Error) -> Self {
+        Error::Regression(e)
+    }
+}
+
+/// Suggested number of lags for portmanteau tests, `min(10, n / 5)` (Hyndman and Athanasopoulos)
+pub fn suggested_lags(observations: usize) -> usize {
+    (observations / 5).clamp(1, 10)
+}
+
+/// Maximum lag considered by automatic lag selection, `floor(12 * (n / 100)^(1/4))` (Schwert, 1989)
+pub fn schwert_max_lag(observations: usize) -> usize {
+    (12.0 * (observations as f64 / 100.0).powf(0.25)) as usize
+}
+
+/// Deterministic terms included in a Dickey-Fuller test regression
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Deterministic {
+    /// No constant or trend, for series with a zero mean
+    None,
+    /// A constant, for series with a non-zero mean
+    #[default]
+    Constant,
+    /// A constant and a linear time trend, for trending series
+    ConstantTrend,
+}
+
+/// How the number of lagged differences in a Dickey-Fuller regression is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LagSelection {
+    /// A fixed number of lags
+    Fixed(usize),
+    /// Minimize the Akaike information criterion, up to `max_lags` (Schwert's rule if `None`)
+    Aic { max_lags: Option<usize> },
+    /// Minimize the Bayesian information criterion, up to `max_lags` (Schwert's rule if `None`)
+    Bic { max_lags: Option<usize> },
+}
+
+/// The outcome of an augmented Dickey-Fuller test
+#[derive(Debug, Clone, Copy)]
+pub struct DickeyFullerResult {
+    pub statistic: f64,
+    /// MacKinnon (1994) approximate p-value
+    pub p_value: f64,
+    /// Number of lagged differences in the test regression
+    pub lags: usize,
+    /// Number of observations used in the test regression
+    pub observations: usize,
+    /// MacKinnon (2010) critical values at the 1%, 5% and 10% levels
+    pub critical_values: [f64; 3],
+}
+
+/// The outcome of a Lo-MacKinlay variance ratio test
+#[derive(Debug, Clone, Copy)]
+pub struct VarianceRatioResult {
+    /// Ratio of the variance of `period`-period returns to `period` times the one-period variance
+    pub ratio: f64,
+    /// Standardized test statistic, asymptotically standard normal
+    pub statistic: f64,
+    /// Two-sided p-value of the null hypothesis that the series is a random walk
+    pub p_value: f64,
+    pub period: usize,
+}
+
+/// MacKinnon (1994) coefficients for the approximate p-value of a Dickey-Fuller statistic,
+/// as `(max, min, star, small-p polynomial, large-p polynomial)`
+fn mackinnon_coefficients(deterministic: Deterministic) -> (f64, f64, f64, [f64; 3], [f64; 4]) {
+    match deterministic {
+        Deterministic::None => (
+            f64::INFINITY,
+            -19.04,
+            -1.04,
+            [0.6344, 1.2378, 3.2496e-2],
+            [0.4797, 9.3557e-1, -0.6999e-1, 3.3066e-2],
+        ),
+        Deterministic::Constant => (
+            2.74,
+            -18.83,
+            -1.61,
+            [2.1659, 1.4412, 3.8269e-2],
+            [1.7339, 9.3202e-1, -1.2745e-1, -1.0368e-2],
+        ),
+        Deterministic::ConstantTrend => (
+            0.7,
+            -16.18,
+            -2.89,
+            [3.2512, 1.6047, 4.9588e-2],
+            [2.5261, 6.1654e-1, -3.7956e-1, -6.0285e-2],
+        ),
+    }
+}
+
+/// MacKinnon (1994) approximate p-value of a Dickey-Fuller statistic
+fn mackinnon_p_value(statistic: f64, deterministic: Deterministic) -> f64 {
+    let (max, min, star, small, large) = mackinnon_coefficients(deterministic);
+
+    if statistic > max {
+        return 1.0;
+    }
+
+    if statistic < min {
+        return 0.0;
+    }
+
+    let polynomial = |coefficients: &[f64]| {
+        coefficients
+            .iter()
+            .rev()
+            .fold(0.0, |acc, c| acc * statistic + c)
+    };
+
+    let z = if statistic <= star {
+        polynomial(&small)
+    } else {
+        polynomial(&large)
+    };
+
+    special::standard_normal_cdf(z)
+}
+
+/// MacKinnon (2010) critical values of a Dickey-Fuller statistic at the 1%, 5% and 10% levels
+fn mackinnon_critical_values(observations: usize, deterministic: Deterministic) -> [f64; 3] {
+    let surfaces = match deterministic {
+        Deterministic::None => [
+            [-2.56574, -2.2358, -3.627, 0.0],
+            [-1.94100, -0.2686, -3.365, 31.223],
+            [-1.61682, 0.2656, -2.714, 25.364],
+        ],
+        Deterministic::Constant => [
+            [-3.43035, -6.5393, -16.786, -79.433],
+            [-2.86154, -2.8903, -4.234, -40.040],
+            [-2.56677, -1.5384, -2.809, 0.0],
+        ],
+        Deterministic::ConstantTrend => [
+            [-3.95877, -9.0531, -28.428, -134.155],
+            [-3.41049, -4.3904, -9.036, -45.374],
+            [-3.12705, -2.5856, -3.925, -22.380],
+        ],
+    };
+
+    let inverse = 1.0 / observations as f64;
+
+    surfaces.map(|b| b[0] + b[1] * inverse + b[2] * inverse.powi(2) + b[3] * inverse.powi(3))
+}
+
+/// Fit the Dickey-Fuller regression of `diff(y)_t` on `y_{t-1}`, `lags` lagged differences and
+/// the deterministic terms, using observations from `start` onwards
+fn dickey_fuller_regression(
+    values: &[f64],
+    lags: usize,
+    start: usize,
+    deterministic: Deterministic,
+) -> Result<RegressionResult, Error> {
+    let differences: Vec<f64> = values.windows(2).map(|w| w[1] - w[0]).collect();
+
+    let response = differences[start..].to_vec();
+    let design: Vec<Vec<f64>> = (start..differences.len())
+        .map(|t| {
+            let mut row = vec![values[t]];
+            row.extend((1..=lags).map(|i| differences[t - i]));
+
+            if deterministic == Deterministic::ConstantTrend {
+                row.push((t + 1) as f64);
+            }
+
+            row
+        })
+        .collect();
+
+    let regression = match deterministic {
+        Deterministic::None => LinearRegression::new().without_intercept(),
+        _ => LinearRegression::new(),
+    };
+
+    Ok(regression.fit(&response, &design)?)
+}
+
+/// Time series autocorrelation, stationarity and serial dependence statistics
+pub trait TimeSeriesStatistics: Statistics {
+    /// Calculate the sample autocorrelations at lags 1 to `max_lag`
+    fn try_autocorrelations(&self, max_lag: usize) -> Result<Vec<f64>, Error> {
+        let values = self.try_values(2)?;
+
+        if max_lag == 0 || max_lag >= values.len() {
+            return Err(Error::InvalidLag(max_lag));
+        }
+
+        let mean = self.try_population_mean()?;
+        let deviations: Vec<f64> = values.iter().map(|v| v - mean).collect();
+        let denominator: f64 = deviations.iter().map(|d| d * d).sum();
+
+        if denominator == 0.0 {
+            return Err(StatsError::DegenerateVariance.into());
+        }
+
+        Ok((1..=max_lag)
+            .map(|lag| {
+                deviations
+                    .iter()
+                    .zip(&deviations[lag..])
+                    .map(|(a, b)| a * b)
+                    .sum::<f64>()
+                    / denominator
+            })
+            .collect())
+    }
+
+    /// Calculate the sample autocorrelations at lags 1 to `max_lag`
+    fn autocorrelations(&self, max_lag: usize) -> Vec<f64> {
+        self.try_autocorrelations(max_lag)
+            .unwrap_or_else(|_| vec![f64::NAN; max_lag])
+    }
+
+    /// Calculate the sample partial autocorrelations at lags 1 to `max_lag` (Durbin-Levinson)
+    fn try_partial_autocorrelations(&self, max_lag: usize) -> Result<Vec<f64>, Error> {
+        let acf = self.try_autocorrelations(max_lag)?;
+        let mut partial = Vec::with_capacity(max_lag);
+        let mut phi: Vec<f64> = vec![];
+
+        for k in 0..max_lag {
+            let numerator = acf[k]
+                - phi
+                    .iter()
+                    .zip(acf[..k].iter().rev())
+                    .map(|(p, r)| p * r)
+                    .sum::<f64>();
+            let denominator = 1.0 - phi.iter().zip(&acf[..k]).map(|(p, r)| p * r).sum::<f64>();
+            let phi_kk = numerator / denominator;
+
+            phi = phi
+                .iter()
+                .zip(phi.iter().rev())
+                .map(|(p, q)| p - phi_kk * q)
+                .chain(std::iter::once(phi_kk))
+                .collect();
+
+            partial.push(phi_kk);
+        }
+
+        Ok(partial)
+    }
+
+    /// Calculate the sample partial autocorrelations at lags 1 to `max_lag`
+    fn partial_autocorrelations(&self, max_lag: usize) -> Vec<f64> {
+        self.try_partial_autocorrelations(max_lag)
+            .unwrap_or_else(|_| vec![f64::NAN; max_lag])
+    }
+
+    /// Ljung-Box test that the first `lags` autocorrelations are jointly zero
+    ///
+    /// When testing the residuals of a fitted model, `fitted_parameters` reduces the degrees of
+    /// freedom of the chi-square reference distribution.
+    fn ljung_box(&self, lags: usize, fitted_parameters: usize) -> Result<TestResult, Error> {
+        let acf = self.try_autocorrelations(lags)?;
+        let n = self.count() as f64;
+
+        let statistic = n
+            * (n + 2.0)
+            * acf
+                .iter()
+                .enumerate()
+                .map(|(i, r)| r * r / (n - (i + 1) as f64))
+                .sum::<f64>();
+
+        portmanteau_result(statistic, lags, fitted_parameters)
+    }
+
+    /// Box-Pierce test that the first `lags` autocorrelations are jointly zero
+    fn box_pierce(&self, lags: usize, fitted_parameters: usize) -> Result<TestResult, Error> {
+        let acf = self.try_autocorrelations(lags)?;
+        let statistic = self.count() as f64 * acf.iter().map(|r| r * r).sum::<f64>();
+
+        portmanteau_result(statistic, lags, fitted_parameters)
+    }
+
+    /// Augmented Dickey-Fuller test of the null hypothesis that the series has a unit root
+    ///
+    /// With automatic lag selection, every candidate regression is fitted on the same sample
+    /// and the chosen one is refitted on all available observations.
+    fn augmented_dickey_fuller(
+        &self,
+        deterministic: Deterministic,
+        lag_selection: LagSelection,
+    ) -> Result<DickeyFullerResult, Error> {
+        let values = self.try_values(3)?;
+        let n = values.len();
+
+        // Each regression needs more observations, `n - 1 - lags`, than its coefficients,
+        // `1 + lags` plus the deterministic terms.
+        let deterministic_terms = match deterministic {
+            Deterministic::None => 0,
+            Deterministic::Constant => 1,
+            Deterministic::ConstantTrend => 2,
+        };
+        let supported_lags = n
+            .checked_sub(3 + deterministic_terms)
+            .map(|excess| excess / 2)
+            .ok_or(StatsError::InsufficientData {
+                required: 3 + deterministic_terms,
+                actual: n,
+            })?;
+        let check = |lags: usize| {
+            if lags > supported_lags {
+                Err(Error::InvalidLag(lags))
+            } else {
+                Ok(lags)
+            }
+        };
+
+        let lags = match lag_selection {
+            LagSelection::Fixed(lags) => check(lags)?,
+            LagSelection::Aic { max_lags } | LagSelection::Bic { max_lags } => {
+                let max_lags = match max_lags {
+                    Some(max_lags) => check(max_lags)?,
+                    None => schwert_max_lag(n).min(supported_lags),
+                };
+                let bic = matches!(lag_selection, LagSelection::Bic { .. });
+                let mut best = (f64::INFINITY, 0);
+
+                for lags in 0..=max_lags {
+                    let fit = dickey_fuller_regression(values, lags, max_lags, deterministic)?;
+                    let observations = fit.residuals.len() as f64;
+                    let parameters = fit.coefficients.len() as f64;
+                    let residual_sum_squares: f64 = fit.residuals.iter().map(|e| e * e).sum();
+                    let penalty = if bic { observations.ln() } else { 2.0 };
+                    let criterion = observations * (residual_sum_squares / observations).ln()
+                        + penalty * parameters;
+
+                    if criterion < best.0 {
+                        best = (criterion, lags);
+                    }
+                }
+
+                best.1
+            }
+        };
+
+        let fit = dickey_fuller_regression(values, lags, lags, deterministic)?;
+
+        // The coefficient of the lagged level follows the intercept, if there is one.
+        let index = usize::from(fit.intercept);
+        let statistic = fit.t_statistics[index];
+        let observations = fit.residuals.len();
+
+        Ok(DickeyFullerResult {
+            statistic,
+            p_value: mackinnon_p_value(statistic, deterministic),
+            lags,
+            observations,
+            critical_values: mackinnon_critical_values(observations, deterministic),
+        })
+    }
+
+    /// Lo-MacKinlay (1988) variance ratio test that a series of returns follows a random walk
+    ///
+    /// With `heteroskedastic`, the statistic is robust to changing volatility.
+    fn variance_ratio(
+        &self,
+        period: usize,
+        heteroskedastic: bool,
+    ) -> Result<VarianceRatioResult, Error> {
+        let values = self.try_values(2)?;
+        let n = values.len();
+
+        if period < 2 || period >= n {
+            return Err(Error::InvalidLag(period));
+        }
+
+        let q = period as f64;
+        let mean = self.try_population_mean()?;
+        let deviations: Vec<f64> = values.iter().map(|v| v - mean).collect();
+        let sum_squares: f64 = deviations.iter().map(|d| d * d).sum();
+
+        if sum_squares == 0.0 {
+            return Err(StatsError::DegenerateVariance.into());
+        }
+
+        // Unbiased one-period variance estimates from one-period and overlapping q-period returns.
+        let variance = sum_squares / (n - 1) as f64;
+        let m = q * (n - period + 1) as f64 * (1.0 - q / n as f64);
+        let variance_q = deviations
+            .windows(period)
+            .map(|w| w.iter().sum::<f64>().powi(2))
+            .sum::<f64>()
+            / m;
+
+        let ratio = variance_q / variance;
+
+        let asymptotic_variance = if heteroskedastic {
+            (1..period)
+                .map(|j| {
+                    let delta = n as f64
+                        * deviations
+                            .iter()
+                            .zip(&deviations[j..])
+                            .map(|(a, b)| a * a * b * b)
+                            .sum::<f64>()
+                        / sum_squares.powi(2);
+
+                    (2.0 * (q - j as f64) / q).powi(2) * delta
+                })
+                .sum::<f64>()
+        } else {
+            2.0 * (2.0 * q - 1.0) * (q - 1.0) / (3.0 * q * n as f64)
+        };
+
+        let statistic = (ratio - 1.0) / asymptotic_variance.sqrt();
+
+        Ok(VarianceRatioResult {
+            ratio,
+            statistic,
+            p_value: 2.0 * (1.0 - special::standard_normal_cdf(statistic.abs())),
+            period,
+        })
+    }
+}
+
+impl<T: Statistics + ?Sized> TimeSeriesStatistics for T {}
+
+/// Compare a portmanteau statistic with a chi-square distribution
+fn portmanteau_result(
+    statistic: f64,
+    lags: usize,
+    fitted_parameters: usize,
+) -> Result<TestResult, Error> {
+    if fitted_parameters >= lags {
+        return Err(Error::InvalidLag(lags));
+    }
+
+    let degrees_of_freedom = (lags - fitted_parameters) as f64;
+    let chi_squared = ChiSquared::new(degrees_of_freedom).map_err(|_| Error::InvalidLag(lags))?;
+
+    Ok(TestResult::new(
+        statistic,
+        1.0 - chi_squared.cdf(statistic),
+        Some(degrees_of_freedom),
+    ))
+}
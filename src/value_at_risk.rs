@@ -0,0 +1,472 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::covariance::{self, CovarianceMatrix, CovarianceMethod};
+use crate::distributions::{self, ChiSquared, ContinuousDistribution};
+use crate::hypothesis::TestResult;
+use crate::matrix;
+use crate::portfolio::Portfolio;
+use crate::quantile::{quantile_sorted, QuantileMethod};
+use crate::special;
+use crate::stats::{Statistics, StatsError};
+
+/// Number of tail probabilities averaged for the Cornish-Fisher expected shortfall
+const CORNISH_FISHER_TAIL_POINTS: usize = 1000;
+
+/// Error type for Value-at-Risk estimates and backtests
+#[derive(Debug)]
+pub enum Error {
+    /// The returns are empty, contain NaN, or have no variance
+    Stats(StatsError),
+    Distribution(distributions::Error),
+    Covariance(covariance::Error),
+    /// Confidence levels must be strictly between 0 and 1
+    InvalidConfidence(f64),
+    /// The horizon must be at least one period
+    InvalidHorizon,
+    /// At least one simulation is required
+    NoSimulations,
+    /// The portfolio has no assets, no amount invested, or no dates on which every asset has a
+    /// return
+    EmptyPortfolio,
+    /// The covariance matrix of the asset returns is not positive definite
+    NotPositiveDefinite,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<StatsError> for Error {
+    fn from(e: StatsError) -> Self {
+        Error::Stats(e)
+    }
+}
+
+impl From<covariance::Error> for Error {
+    fn from(e: covariance::Error) -> Self {
+        Error::Covariance(e)
+    }
+}
+
+impl From<distributions::Error> for Error {
+    fn from(e: distributions::Error) -> Self {
+        Error::Distribution(e)
+    }
+}
+
+/// Method used to estimate the distribution of returns over the horizon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VarMethod {
+    /// Empirical quantile of overlapping compounded returns over the horizon
+    Historical,
+    /// Normal returns, with the mean and variance scaled by the horizon
+    Normal,
+    /// Normal quantile adjusted for the skewness and excess kurtosis of the returns, which are
+    /// scaled by the horizon as for independent returns
+    CornishFisher,
+    /// Compounded paths of normal returns with the sample mean and (co)variance
+    MonteCarlo { simulations: usize, seed: u64 },
+}
+
+/// Value-at-Risk and Expected Shortfall, as positive fractions of the value at risk
+#[derive(Debug, Clone, Copy)]
+pub struct RiskEstimate {
+    /// Loss that is not exceeded with probability `confidence`
+    pub value_at_risk: f64,
+    /// Average loss in the worst `1 - confidence` of outcomes
+    pub expected_shortfall: f64,
+    pub confidence: f64,
+    /// Number of periods the returns are held for
+    pub horizon: usize,
+}
+
+impl RiskEstimate {
+    /// Convert the estimate to amounts for a position of the given value
+    pub fn scale(&self, value: f64) -> Self {
+        Self {
+            value_at_risk: self.value_at_risk * value,
+            expected_shortfall: self.expected_shortfall * value,
+            ..*self
+        }
+    }
+}
+
+/// Value-at-Risk model
+#[derive(Debug, Clone, Copy)]
+pub struct ValueAtRisk {
+    pub method: VarMethod,
+    /// Probability that a loss does not exceed the Value-at-Risk, e.g. 0.99
+    pub confidence: f64,
+    /// Number of periods the returns are held for
+    pub horizon: usize,
+}
+
+/// Calculate the loss at the `1 - confidence` quantile and the average loss beyond it
+fn empirical_tail(mut outcomes: Vec<f64>, confidence: f64) -> Result<(f64, f64), StatsError> {
+    outcomes.sort_by(|a, b| a.total_cmp(b));
+
+    let quantile = quantile_sorted(&outcomes, 1.0 - confidence, QuantileMethod::default())?;
+    let tail: Vec<f64> = outcomes
+        .iter()
+        .copied()
+        .filter(|r| *r <= quantile)
+        .collect();
+    let shortfall = tail.iter().sum::<f64>() / tail.len() as f64;
+
+    Ok((-quantile, -shortfall))
+}
+
+/// Compound overlapping windows of returns over the horizon
+fn compound_windows(returns: &[f64], horizon: usize) -> Vec<f64> {
+    returns
+        .windows(horizon)
+        .map(|w| w.iter().map(|r| 1.0 + r).product::<f64>() - 1.0)
+        .collect()
+}
+
+/// Adjust a standard normal quantile for skewness and excess kurtosis
+fn cornish_fisher(z: f64, skewness: f64, excess_kurtosis: f64) -> f64 {
+    z + (z * z - 1.0) * skewness / 6.0 + (z.powi(3) - 3.0 * z) * excess_kurtosis / 24.0
+        - (2.0 * z.powi(3) - 5.0 * z) * skewness * skewness / 36.0
+}
+
+/// Draw a standard normal value
+fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    // Uniform draws lie in [0, 1), and the quantile of zero is negative infinity.
+    loop {
+        let u = rng.gen::<f64>();
+
+        if u > 0.0 {
+            return special::standard_normal_inverse_cdf(u);
+        }
+    }
+}
+
+impl ValueAtRisk {
+    /// Create a one-period Value-at-Risk model
+    pub fn new(method: VarMethod, confidence: f64) -> Self {
+        Self {
+            method,
+            confidence,
+            horizon: 1,
+        }
+    }
+
+    /// Hold the returns over a number of periods
+    pub fn with_horizon(mut self, horizon: usize) -> Self {
+        self.horizon = horizon;
+        self
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if !(self.confidence > 0.0 && self.confidence < 1.0) {
+            return Err(Error::InvalidConfidence(self.confidence));
+        }
+
+        if self.horizon == 0 {
+            return Err(Error::InvalidHorizon);
+        }
+
+        if matches!(self.method, VarMethod::MonteCarlo { simulations: 0, .. }) {
+            return Err(Error::NoSimulations);
+        }
+
+        Ok(())
+    }
+
+    /// Estimate from the moments of the returns, for the parametric methods
+    fn parametric(
+        &self,
+        mean: f64,
+        std_dev: f64,
+        skewness: f64,
+        excess_kurtosis: f64,
+    ) -> RiskEstimate {
+        let horizon = self.horizon as f64;
+        let alpha = 1.0 - self.confidence;
+        let mean = mean * horizon;
+        let std_dev = std_dev * horizon.sqrt();
+        let z = special::standard_normal_inverse_cdf(alpha);
+
+        let (value_at_risk, expected_shortfall) = match self.method {
+            VarMethod::CornishFisher => {
+                let skewness = skewness / horizon.sqrt();
+                let excess_kurtosis = excess_kurtosis / horizon;
+                let quantile = |p: f64| {
+                    mean + std_dev
+                        * cornish_fisher(
+                            special::standard_normal_inverse_cdf(p),
+                            skewness,
+                            excess_kurtosis,
+                        )
+                };
+
+                // Expected shortfall is the average of the quantiles in the tail.
+                let points = CORNISH_FISHER_TAIL_POINTS;
+                let tail = (0..points)
+                    .map(|i| quantile(alpha * (i as f64 + 0.5) / points as f64))
+                    .sum::<f64>()
+                    / points as f64;
+
+                (-quantile(alpha), -tail)
+            }
+            _ => (
+                -(mean + std_dev * z),
+                -(mean - std_dev * special::standard_normal_pdf(z) / alpha),
+            ),
+        };
+
+        RiskEstimate {
+            value_at_risk,
+            expected_shortfall,
+            confidence: self.confidence,
+            horizon: self.horizon,
+        }
+    }
+
+    fn empirical(&self, outcomes: Vec<f64>) -> Result<RiskEstimate, Error> {
+        let (value_at_risk, expected_shortfall) = empirical_tail(outcomes, self.confidence)?;
+
+        Ok(RiskEstimate {
+            value_at_risk,
+            expected_shortfall,
+            confidence: self.confidence,
+            horizon: self.horizon,
+        })
+    }
+
+    /// Estimate the Value-at-Risk and Expected Shortfall of a series of periodic returns
+    pub fn estimate<S: Statistics + ?Sized>(&self, returns: &S) -> Result<RiskEstimate, Error> {
+        self.validate()?;
+
+        let values = returns.try_values(self.horizon.max(2))?;
+
+        match self.method {
+            VarMethod::Historical => self.empirical(compound_windows(values, self.horizon)),
+            VarMethod::Normal => Ok(self.parametric(
+                returns.try_population_mean()?,
+                returns.try_nonzero_std_dev()?,
+                0.0,
+                0.0,
+            )),
+            VarMethod::CornishFisher => Ok(self.parametric(
+                returns.try_population_mean()?,
+                returns.try_nonzero_std_dev()?,
+                returns.try_skewness()?,
+                returns.try_excess_kurtosis()?,
+            )),
+            VarMethod::MonteCarlo { simulations, seed } => {
+                let mean = returns.try_population_mean()?;
+                let std_dev = returns.try_nonzero_std_dev()?;
+                let mut rng = StdRng::seed_from_u64(seed);
+
+                let outcomes = (0..simulations)
+                    .map(|_| {
+                        (0..self.horizon)
+                            .map(|_| 1.0 + mean + std_dev * standard_normal(&mut rng))
+                            .product::<f64>()
+                            - 1.0
+                    })
+                    .collect();
+
+                self.empirical(outcomes)
+            }
+        }
+    }
+
+    /// Estimate the Value-at-Risk and Expected Shortfall of a portfolio, weighting the assets
+    /// by the amount invested
+    ///
+    /// Returns are aligned on the dates every asset has a return. Parametric estimates use the
+    /// sample covariance matrix of the asset returns, with the higher moments of the
+    /// Cornish-Fisher expansion taken from the weighted portfolio returns.
+    ///
+    /// Over a horizon of several periods, the portfolio is rebalanced to its weights every
+    /// period: historical and simulated outcomes both compound the weighted portfolio return.
+    pub fn estimate_portfolio(&self, portfolio: &Portfolio) -> Result<RiskEstimate, Error> {
+        self.validate()?;
+
+        let total: f64 = portfolio.assets.values().map(|a| a.amount_invested).sum();
+        let returns = portfolio.aligned_returns();
+
+        if total == 0.0 || returns.is_empty() {
+            return Err(Error::EmptyPortfolio);
+        }
+
+        let weights = portfolio.weights();
+        let portfolio_returns = portfolio.returns();
+
+        if self.method == VarMethod::Historical {
+            return self.estimate(&portfolio_returns);
+        }
+
+        let columns: Vec<Vec<f64>> = (0..weights.len())
+            .map(|i| returns.iter().map(|(_, r)| r[i]).collect())
+            .collect();
+        let means: Vec<f64> = columns
+            .iter()
+            .map(|c| c.try_population_mean())
+            .collect::<Result<_, _>>()?;
+        let covariance =
+            CovarianceMatrix::estimate(portfolio.tickers(), &columns, CovarianceMethod::Sample)?
+                .matrix;
+
+        match self.method {
+            VarMethod::MonteCarlo { simulations, seed } => {
+                let lower = matrix::cholesky(&covariance).ok_or(Error::NotPositiveDefinite)?;
+                let mut rng = StdRng::seed_from_u64(seed);
+                let mut shocks = vec![0.0; weights.len()];
+
+                let outcomes = (0..simulations)
+                    .map(|_| {
+                        (0..self.horizon)
+                            .map(|_| {
+                                shocks
+                                    .iter_mut()
+                                    .for_each(|z| *z = standard_normal(&mut rng));
+
+                                1.0 + matrix::multiply_vector(&lower, &shocks)
+                                    .iter()
+                                    .zip(&means)
+                                    .zip(&weights)
+                                    .map(|((correlated, mean), w)| w * (mean + correlated))
+                                    .sum::<f64>()
+                            })
+                            .product::<f64>()
+                            - 1.0
+                    })
+                    .collect();
+
+                self.empirical(outcomes)
+            }
+            _ => {
+                let mean: f64 = means.iter().zip(&weights).map(|(m, w)| m * w).sum();
+                let std_dev = matrix::quadratic_form(&covariance, &weights).sqrt();
+
+                if std_dev == 0.0 {
+                    return Err(StatsError::DegenerateVariance.into());
+                }
+
+                let (skewness, excess_kurtosis) = if self.method == VarMethod::CornishFisher {
+                    (
+                        portfolio_returns.try_skewness()?,
+                        portfolio_returns.try_excess_kurtosis()?,
+                    )
+                } else {
+                    (0.0, 0.0)
+                };
+
+                Ok(self.parametric(mean, std_dev, skewness, excess_kurtosis))
+            }
+        }
+    }
+}
+
+/// Flag the periods in which the loss exceeds the Value-at-Risk forecast for that period
+pub fn exceptions(returns: &[f64], value_at_risk: &[f64]) -> Result<Vec<bool>, Error> {
+    if returns.len() != value_at_risk.len() {
+        return Err(StatsError::LengthMismatch {
+            expected: returns.len(),
+            actual: value_at_risk.len(),
+        }
+        .into());
+    }
+
+    if returns.is_empty() {
+        return Err(StatsError::EmptyInput.into());
+    }
+
+    Ok(returns
+        .iter()
+        .zip(value_at_risk)
+        .map(|(r, var)| -r > *var)
+        .collect())
+}
+
+/// Log-likelihood of `hits` successes in `trials` Bernoulli trials, taking `0 ln 0` as zero
+fn bernoulli_log_likelihood(hits: f64, trials: f64, probability: f64) -> f64 {
+    let term = |count: f64, p: f64| if count == 0.0 { 0.0 } else { count * p.ln() };
+
+    term(hits, probability) + term(trials - hits, 1.0 - probability)
+}
+
+/// Likelihood ratio test result against a chi-squared distribution
+fn likelihood_ratio(statistic: f64, degrees_of_freedom: f64) -> Result<TestResult, Error> {
+    let statistic = statistic.max(0.0);
+    let p_value = 1.0 - ChiSquared::new(degrees_of_freedom)?.cdf(statistic);
+
+    Ok(TestResult::new(
+        statistic,
+        p_value,
+        Some(degrees_of_freedom),
+    ))
+}
+
+/// Kupiec (1995) proportion of failures test that the rate of Value-at-Risk exceptions matches
+/// `1 - confidence`
+pub fn kupiec_test(
+    returns: &[f64],
+    value_at_risk: &[f64],
+    confidence: f64,
+) -> Result<TestResult, Error> {
+    if !(confidence > 0.0 && confidence < 1.0) {
+        return Err(Error::InvalidConfidence(confidence));
+    }
+
+    let hits = exceptions(returns, value_at_risk)?;
+    let trials = hits.len() as f64;
+    let failures = hits.iter().filter(|h| **h).count() as f64;
+
+    let statistic = -2.0
+        * (bernoulli_log_likelihood(failures, trials, 1.0 - confidence)
+            - bernoulli_log_likelihood(failures, trials, failures / trials));
+
+    likelihood_ratio(statistic, 1.0)
+}
+
+/// Result of the Christoffersen (1998) Value-at-Risk backtest
+#[derive(Debug, Clone, Copy)]
+pub struct ChristoffersenResult {
+    /// Test that exceptions are independent of whether there was an exception the period before
+    pub independence: TestResult,
+    /// Joint test of the exception rate and independence
+    pub conditional_coverage: TestResult,
+}
+
+/// Christoffersen (1998) tests of independence and conditional coverage of Value-at-Risk
+/// exceptions, against a first-order Markov alternative
+pub fn christoffersen_test(
+    returns: &[f64],
+    value_at_risk: &[f64],
+    confidence: f64,
+) -> Result<ChristoffersenResult, Error> {
+    let unconditional = kupiec_test(returns, value_at_risk, confidence)?;
+    let hits = exceptions(returns, value_at_risk)?;
+
+    // Transition counts n_ij from state i to state j, where an exception is state 1.
+    let mut transitions = [[0.0; 2]; 2];
+
+    for w in hits.windows(2) {
+        transitions[usize::from(w[0])][usize::from(w[1])] += 1.0;
+    }
+
+    let [[n00, n01], [n10, n11]] = transitions;
+    let total = n00 + n01 + n10 + n11;
+
+    let markov = bernoulli_log_likelihood(n01, n00 + n01, n01 / (n00 + n01))
+        + bernoulli_log_likelihood(n11, n10 + n11, n11 / (n10 + n11));
+    let independent = bernoulli_log_likelihood(n01 + n11, total, (n01 + n11) / total);
+
+    let independence = likelihood_ratio(-2.0 * (independent - markov), 1.0)?;
+    let conditional_coverage =
+        likelihood_ratio(unconditional.statistic + independence.statistic, 2.0)?;
+
+    Ok(ChristoffersenResult {
+        independence,
+        conditional_coverage,
+    })
+}